@@ -0,0 +1,257 @@
+//!
+//! Cancellation of operations using JavaScript [`AbortController`] and
+//! [`AbortSignal`]. Signals can cancel timers ([`set_timeout()`], [`set_interval()`]),
+//! remove event listeners ([`add_event_listener()`]) and cancel Rust futures
+//! ([`abortable()`]), including signals supplied by JavaScript code.
+//!
+//! ```ignore
+//! let controller = AbortController::new()?;
+//! let signal = controller.signal();
+//!
+//! let _timeout = abort::set_timeout(&closure, 1000, &signal)?;
+//! let result = abort::abortable(&signal, fetch(url)).await;
+//!
+//! controller.abort();
+//! ```
+//!
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use js_sys::{Function, Object, Reflect};
+use thiserror::Error;
+use crate::callback::Callback;
+use crate::promise::resolvable;
+use crate::timers::{self, IntervalHandle, TimeoutHandle};
+use crate::utils::{self, call_method};
+
+/// Errors produced by the [`abort`](self) module
+#[derive(Error, Debug)]
+pub enum AbortError {
+    /// Error contains a JsValue
+    #[error("{}", crate::utils::describe(.0, 2))]
+    JsValue(JsValue),
+
+    /// Operation was aborted with the contained reason
+    #[error("Operation aborted: {}", crate::utils::describe(.0, 1))]
+    Aborted(JsValue),
+
+    #[error(transparent)]
+    Timers(#[from] timers::Error),
+
+    #[error(transparent)]
+    Utils(#[from] utils::Error),
+}
+
+impl From<JsValue> for AbortError {
+    fn from(value: JsValue) -> Self {
+        AbortError::JsValue(value)
+    }
+}
+
+impl From<AbortError> for JsValue {
+    fn from(err: AbortError) -> Self {
+        crate::error::Error::from(err).into()
+    }
+}
+
+pub type AbortResult<T> = std::result::Result<T, AbortError>;
+
+#[wasm_bindgen]
+extern "C" {
+    /// [`mod@wasm_bindgen`] binding to the JavaScript [`AbortController`](https://developer.mozilla.org/en-US/docs/Web/API/AbortController) class
+    #[wasm_bindgen(js_name = AbortController)]
+    #[derive(Debug, Clone)]
+    pub type AbortController;
+
+    #[wasm_bindgen(constructor, catch, js_class = AbortController)]
+    pub fn new() -> std::result::Result<AbortController, JsValue>;
+
+    /// Signal controlled by this controller
+    #[wasm_bindgen(method, getter)]
+    pub fn signal(this: &AbortController) -> AbortSignal;
+
+    /// Abort the signal (with an `AbortError` `DOMException` as the reason)
+    #[wasm_bindgen(method)]
+    pub fn abort(this: &AbortController);
+
+    /// Abort the signal with `reason`
+    #[wasm_bindgen(method, js_name = abort)]
+    pub fn abort_with_reason(this: &AbortController, reason: &JsValue);
+
+    /// [`mod@wasm_bindgen`] binding to the JavaScript [`AbortSignal`](https://developer.mozilla.org/en-US/docs/Web/API/AbortSignal) class
+    #[wasm_bindgen(js_name = AbortSignal)]
+    #[derive(Debug, Clone)]
+    pub type AbortSignal;
+
+    /// Check if the signal has been aborted
+    #[wasm_bindgen(method, getter)]
+    pub fn aborted(this: &AbortSignal) -> bool;
+
+    /// Abort reason (`undefined` if not aborted or not supported by the runtime)
+    #[wasm_bindgen(method, getter)]
+    pub fn reason(this: &AbortSignal) -> JsValue;
+
+    #[wasm_bindgen(method, js_name = addEventListener)]
+    fn add_event_listener_with_options(this: &AbortSignal, event: &str, listener: &Function, options: &JsValue);
+
+    #[wasm_bindgen(method, js_name = removeEventListener)]
+    fn remove_event_listener(this: &AbortSignal, event: &str, listener: &Function);
+}
+
+/// `abort` event listener, dropping which removes the listener from the signal
+pub struct AbortListener {
+    signal: AbortSignal,
+    callback: Callback<dyn FnMut(JsValue)>,
+}
+
+impl Drop for AbortListener {
+    fn drop(&mut self) {
+        self.signal.remove_event_listener("abort", self.callback.as_ref());
+    }
+}
+
+/// Invoke `handler` with the abort reason when `signal` is aborted
+/// (immediately, if the signal is already aborted).
+pub fn on_abort<F>(signal: &AbortSignal, handler: F) -> AbortListener
+where F: FnOnce(JsValue) + 'static
+{
+    let mut handler = Some(handler);
+    let target = signal.clone();
+    let callback = Callback::new(move |_event: JsValue| {
+        if let Some(handler) = handler.take() {
+            handler(target.reason());
+        }
+    });
+
+    let options = Object::new();
+    let _ = Reflect::set(&options, &"once".into(), &JsValue::TRUE);
+    signal.add_event_listener_with_options("abort", callback.as_ref(), &options);
+    if signal.aborted() {
+        let _ = AsRef::<Function>::as_ref(&callback).call1(&JsValue::UNDEFINED, &JsValue::UNDEFINED);
+    }
+
+    AbortListener {
+        signal: signal.clone(),
+        callback,
+    }
+}
+
+/// Return [`AbortError::Aborted`] if `signal` has been aborted
+pub fn check(signal: &AbortSignal) -> AbortResult<()> {
+    if signal.aborted() {
+        Err(AbortError::Aborted(signal.reason()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Timeout created by [`set_timeout()`], cleared when the signal is aborted
+/// or when this handle is dropped
+pub struct AbortableTimeout {
+    handle: TimeoutHandle,
+    _listener: AbortListener,
+}
+
+impl AbortableTimeout {
+    /// Underlying timeout handle
+    pub fn handle(&self) -> &TimeoutHandle {
+        &self.handle
+    }
+}
+
+/// Interval created by [`set_interval()`], cleared when the signal is aborted
+/// or when this handle is dropped
+pub struct AbortableInterval {
+    handle: IntervalHandle,
+    _listener: AbortListener,
+}
+
+impl AbortableInterval {
+    /// Underlying interval handle
+    pub fn handle(&self) -> &IntervalHandle {
+        &self.handle
+    }
+}
+
+/// Create a JavaScript timeout (see [`timers::set_timeout()`]) cleared when `signal` is aborted.
+/// Returns [`AbortError::Aborted`] if `signal` is already aborted.
+pub fn set_timeout(closure: &Closure<dyn FnMut()>, timeout: u32, signal: &AbortSignal) -> AbortResult<AbortableTimeout> {
+    check(signal)?;
+    let handle = timers::set_timeout(closure, timeout)?;
+    let target = handle.clone();
+    let listener = on_abort(signal, move |_| {
+        let _ = timers::clear_timeout(&target);
+    });
+    Ok(AbortableTimeout { handle, _listener: listener })
+}
+
+/// Create a JavaScript interval (see [`timers::set_interval()`]) cleared when `signal` is aborted.
+/// Returns [`AbortError::Aborted`] if `signal` is already aborted.
+pub fn set_interval(closure: &Closure<dyn FnMut()>, timeout: u32, signal: &AbortSignal) -> AbortResult<AbortableInterval> {
+    check(signal)?;
+    let handle = timers::set_interval(closure, timeout)?;
+    let target = handle.clone();
+    let listener = on_abort(signal, move |_| {
+        let _ = timers::clear_interval(&target);
+    });
+    Ok(AbortableInterval { handle, _listener: listener })
+}
+
+/// Register `callback` as a listener of `event` on `target` (any JavaScript
+/// `EventTarget`). The listener is removed when `signal` is aborted.
+pub fn add_event_listener<C>(target: &JsValue, event: &str, callback: &C, signal: &AbortSignal) -> AbortResult<()>
+where C: AsRef<Function>
+{
+    check(signal)?;
+    let options = Object::new();
+    Reflect::set(&options, &"signal".into(), signal)?;
+    call_method(target, "addEventListener", (event, callback.as_ref().clone(), options))?;
+    Ok(())
+}
+
+/// Wait until `signal` is aborted, returning the abort reason
+pub async fn aborted(signal: &AbortSignal) -> JsValue {
+    if signal.aborted() {
+        return signal.reason();
+    }
+
+    let (promise, resolver) = resolvable();
+    let _listener = on_abort(signal, move |reason| resolver.resolve(&reason));
+    JsFuture::from(promise).await.unwrap_or_else(|err| err)
+}
+
+/// Future returned by [`abortable()`]
+struct Abortable<F, A> {
+    future: Pin<Box<F>>,
+    aborted: Pin<Box<A>>,
+}
+
+impl<F, A> Future for Abortable<F, A>
+where
+    F: Future,
+    A: Future<Output = JsValue>,
+{
+    type Output = AbortResult<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(reason) = self.aborted.as_mut().poll(cx) {
+            return Poll::Ready(Err(AbortError::Aborted(reason)));
+        }
+        self.future.as_mut().poll(cx).map(Ok)
+    }
+}
+
+/// Run `future` until it completes or `signal` is aborted, in which case
+/// `future` is dropped and [`AbortError::Aborted`] is returned.
+pub fn abortable<F>(signal: &AbortSignal, future: F) -> impl Future<Output = AbortResult<F::Output>>
+where F: Future
+{
+    let signal = signal.clone();
+    Abortable {
+        future: Box::pin(future),
+        aborted: Box::pin(async move { aborted(&signal).await }),
+    }
+}
@@ -0,0 +1,293 @@
+//!
+//! Conversion of JavaScript buffers (`ArrayBuffer`, typed arrays, `DataView`,
+//! Node.js `Buffer` and arrays of numbers) into Rust slices and vectors,
+//! as well as typed array views over WASM memory.
+//!
+//! ```ignore
+//! // copy bytes from any buffer-like value
+//! let bytes = buffer::to_vec::<u8>(&value)?;
+//!
+//! // copy into an existing slice without allocating
+//! let mut key = [0u8; 32];
+//! buffer::copy_into(&value, &mut key)?;
+//!
+//! // typed variants
+//! let samples = buffer::to_vec::<f32>(&value)?;
+//! ```
+//!
+
+use wasm_bindgen::prelude::*;
+use js_sys::{
+    Array, ArrayBuffer, DataView,
+    Float32Array, Float64Array, Int16Array, Int32Array, Int8Array,
+    Uint16Array, Uint32Array, Uint8Array,
+};
+use thiserror::Error;
+use crate::utils::{self, type_name, FromJsProp};
+
+/// Errors produced by the [`buffer`](self) module
+#[derive(Error, Debug)]
+pub enum Error {
+    /// Error contains a JsValue
+    #[error("{}", crate::utils::describe(.0, 2))]
+    JsValue(JsValue),
+
+    /// Value is not a supported buffer type
+    #[error("Expected {expected}, found {found}")]
+    Type {
+        expected: &'static str,
+        found: String,
+    },
+
+    /// Buffer byte length is not a multiple of the element size
+    #[error("Buffer byte length {byte_length} is not a multiple of {element_size} (`{ty}` element size)")]
+    Length {
+        byte_length: u32,
+        element_size: usize,
+        ty: &'static str,
+    },
+
+    /// Destination slice is too small to receive the buffer data
+    #[error("Destination holds {available} elements, buffer contains {required}")]
+    Capacity {
+        required: usize,
+        available: usize,
+    },
+
+    /// Array element could not be converted
+    #[error(transparent)]
+    Element(#[from] utils::Error),
+}
+
+impl From<JsValue> for Error {
+    fn from(value: JsValue) -> Self {
+        Error::JsValue(value)
+    }
+}
+
+impl From<Error> for JsValue {
+    fn from(err: Error) -> Self {
+        crate::error::Error::from(err).into()
+    }
+}
+
+///
+/// Rust element types that have a corresponding JavaScript typed array
+/// (`u8` - `Uint8Array`, `f32` - `Float32Array`, etc.)
+///
+pub trait BufferElement: FromJsProp + Copy {
+    /// Typed array type holding elements of this type
+    type TypedArray: JsCast + Clone;
+    /// Name of the JavaScript typed array used in error messages
+    const TYPED_ARRAY_NAME: &'static str;
+
+    /// Create a typed array over the entire `buffer`
+    fn from_array_buffer(buffer: &ArrayBuffer) -> Self::TypedArray;
+
+    /// Indicates that [`from_view`](BufferElement::from_view) accepts
+    /// any `ArrayBuffer` view
+    const ACCEPTS_VIEWS: bool = false;
+
+    /// Create a typed array over the bytes of another `ArrayBuffer` view
+    /// (`DataView` or typed array of a different type). Only `u8`
+    /// supports this conversion.
+    fn from_view(_view: &JsValue) -> Option<Self::TypedArray> {
+        None
+    }
+
+    /// Create a typed array from a slice (copies the data)
+    fn from_slice(data: &[Self]) -> Self::TypedArray;
+
+    /// Number of elements in the typed array
+    fn length(array: &Self::TypedArray) -> u32;
+
+    /// Copy typed array contents into a `Vec`
+    fn to_vec(array: &Self::TypedArray) -> Vec<Self>;
+
+    /// Copy typed array contents into `dst` (lengths must be equal)
+    fn copy_to(array: &Self::TypedArray, dst: &mut [Self]);
+
+    /// Create a typed array view over WASM memory (see [`view`])
+    ///
+    /// # Safety
+    /// See [`view`]
+    unsafe fn view(data: &[Self]) -> Self::TypedArray;
+}
+
+macro_rules! impl_buffer_element {
+    ($(
+        ($ty:ty, $array:ident)
+    )*) => ($(
+        impl BufferElement for $ty {
+            type TypedArray = $array;
+            const TYPED_ARRAY_NAME: &'static str = stringify!($array);
+
+            fn from_array_buffer(buffer: &ArrayBuffer) -> $array {
+                $array::new(buffer)
+            }
+
+            fn from_slice(data: &[$ty]) -> $array {
+                $array::from(data)
+            }
+
+            fn length(array: &$array) -> u32 {
+                array.length()
+            }
+
+            fn to_vec(array: &$array) -> Vec<$ty> {
+                array.to_vec()
+            }
+
+            fn copy_to(array: &$array, dst: &mut [$ty]) {
+                array.copy_to(dst)
+            }
+
+            unsafe fn view(data: &[$ty]) -> $array {
+                $array::view(data)
+            }
+        }
+    )*)
+}
+
+impl_buffer_element! {
+    (i8, Int8Array)
+    (i16, Int16Array)
+    (u16, Uint16Array)
+    (i32, Int32Array)
+    (u32, Uint32Array)
+    (f32, Float32Array)
+    (f64, Float64Array)
+}
+
+impl BufferElement for u8 {
+    type TypedArray = Uint8Array;
+    const TYPED_ARRAY_NAME: &'static str = "Uint8Array";
+    const ACCEPTS_VIEWS: bool = true;
+
+    fn from_array_buffer(buffer: &ArrayBuffer) -> Uint8Array {
+        Uint8Array::new(buffer)
+    }
+
+    fn from_view(view: &JsValue) -> Option<Uint8Array> {
+        let buffer = js_sys::Reflect::get(view, &JsValue::from("buffer")).ok()?;
+        let byte_offset = js_sys::Reflect::get(view, &JsValue::from("byteOffset")).ok()?.as_f64()?;
+        let byte_length = js_sys::Reflect::get(view, &JsValue::from("byteLength")).ok()?.as_f64()?;
+        Some(Uint8Array::new_with_byte_offset_and_length(
+            &buffer,
+            byte_offset as u32,
+            byte_length as u32,
+        ))
+    }
+
+    fn from_slice(data: &[u8]) -> Uint8Array {
+        Uint8Array::from(data)
+    }
+
+    fn length(array: &Uint8Array) -> u32 {
+        array.length()
+    }
+
+    fn to_vec(array: &Uint8Array) -> Vec<u8> {
+        array.to_vec()
+    }
+
+    fn copy_to(array: &Uint8Array, dst: &mut [u8]) {
+        array.copy_to(dst)
+    }
+
+    unsafe fn view(data: &[u8]) -> Uint8Array {
+        Uint8Array::view(data)
+    }
+}
+
+/// Obtain a typed array for `value` without copying buffer data. Accepts
+/// a typed array of the matching type, an `ArrayBuffer` (reinterpreted as
+/// elements of type `T`), for `u8` any `ArrayBuffer` view (typed arrays,
+/// `DataView`, Node.js `Buffer`) and arrays of numbers (validated and copied).
+pub fn typed_array<T: BufferElement>(value: &JsValue) -> Result<T::TypedArray, Error> {
+    if let Some(array) = value.dyn_ref::<T::TypedArray>() {
+        return Ok(array.clone());
+    }
+
+    if let Some(buffer) = value.dyn_ref::<ArrayBuffer>() {
+        let byte_length = buffer.byte_length();
+        let element_size = std::mem::size_of::<T>();
+        // `usize::is_multiple_of()` requires Rust 1.87
+        #[allow(clippy::manual_is_multiple_of)]
+        if byte_length as usize % element_size != 0 {
+            return Err(Error::Length {
+                byte_length,
+                element_size,
+                ty: T::TYPED_ARRAY_NAME,
+            });
+        }
+        return Ok(T::from_array_buffer(buffer));
+    }
+
+    if T::ACCEPTS_VIEWS && (ArrayBuffer::is_view(value) || value.is_instance_of::<DataView>()) {
+        if let Some(array) = T::from_view(value) {
+            return Ok(array);
+        }
+    }
+
+    if Array::is_array(value) {
+        let data = T::vec_from_js_prop("<array>", value.clone())?;
+        return Ok(T::from_slice(&data));
+    }
+
+    Err(Error::Type {
+        expected: expected::<T>(),
+        found: type_name(value),
+    })
+}
+
+fn expected<T: BufferElement>() -> &'static str {
+    if T::ACCEPTS_VIEWS {
+        "an ArrayBuffer, a typed array, a DataView, a Buffer or an array of numbers"
+    } else {
+        "an ArrayBuffer, a matching typed array or an array of numbers"
+    }
+}
+
+/// Copy the contents of a buffer-like `value` (see [`typed_array`]) into a `Vec`.
+pub fn to_vec<T: BufferElement>(value: &JsValue) -> Result<Vec<T>, Error> {
+    Ok(T::to_vec(&typed_array::<T>(value)?))
+}
+
+/// Copy the contents of a buffer-like `value` (see [`typed_array`]) into
+/// the beginning of `dst` without allocating. Returns the number of
+/// elements copied or [`Error::Capacity`] if `dst` is too small.
+pub fn copy_into<T: BufferElement>(value: &JsValue, dst: &mut [T]) -> Result<usize, Error> {
+    let array = typed_array::<T>(value)?;
+    let len = T::length(&array) as usize;
+    if len > dst.len() {
+        return Err(Error::Capacity {
+            required: len,
+            available: dst.len(),
+        });
+    }
+
+    T::copy_to(&array, &mut dst[..len]);
+    Ok(len)
+}
+
+/// Create a JavaScript typed array (`Uint8Array`, `Float32Array` etc.)
+/// holding a copy of `data`.
+pub fn from_slice<T: BufferElement>(data: &[T]) -> T::TypedArray {
+    T::from_slice(data)
+}
+
+///
+/// Create a JavaScript typed array that views `data` directly in WASM memory.
+///
+/// # Safety
+///
+/// The returned view is valid only as long as `data` is not moved, modified
+/// or dropped, and only until WASM memory grows (any allocation can grow
+/// the memory, detaching the underlying `ArrayBuffer`). The view must not
+/// outlive the immediate use, such as passing it to a JavaScript function
+/// that consumes the data synchronously.
+///
+pub unsafe fn view<T: BufferElement>(data: &[T]) -> T::TypedArray {
+    T::view(data)
+}
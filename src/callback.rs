@@ -25,7 +25,7 @@ pub enum CallbackError {
     String(String),
 
     /// Error contains a JsValue
-    #[error("JsValue {0:?}")]
+    #[error("{}", crate::utils::describe(.0, 2))]
     JsValue(JsValue),
 
     /// LockError message resulting from Mutex lock failure ([`std::sync::PoisonError`])
@@ -45,7 +45,7 @@ impl From<JsValue> for CallbackError {
 
 impl From<CallbackError> for JsValue {
     fn from(err: CallbackError) -> Self {
-        JsValue::from_str(&err.to_string())
+        crate::error::Error::from(err).into()
     }
 }
 
@@ -0,0 +1,186 @@
+//!
+//! Detection of the JavaScript runtime environment (browser main thread,
+//! web workers, Node.js, Deno, Bun, Electron and NW.js) and of the
+//! capabilities available in it.
+//!
+//! ```ignore
+//! match env::runtime() {
+//!     Runtime::Browser => { /* use the DOM */ },
+//!     runtime if runtime.is_worker() => { /* post messages */ },
+//!     _ => { }
+//! }
+//!
+//! if env::capabilities().crypto_subtle {
+//!     // use WebCrypto
+//! }
+//! ```
+//!
+//! Detection is performed once, on first use, by inspecting the
+//! JavaScript global object (`js_sys::global()`).
+//!
+
+use crate::utils::{is_instance_of_global, resolve_global_path};
+
+/// JavaScript runtime environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Runtime {
+    /// Browser main thread (`window` and `document` are available)
+    Browser,
+    /// Dedicated web worker
+    DedicatedWorker,
+    /// Shared web worker
+    SharedWorker,
+    /// Service worker
+    ServiceWorker,
+    /// Node.js
+    Node,
+    /// Deno
+    Deno,
+    /// Bun
+    Bun,
+    /// Electron (main or renderer process)
+    Electron,
+    /// NW.js
+    NwJs,
+    /// Unrecognized environment
+    Unknown,
+}
+
+impl Runtime {
+    /// Check if the runtime is a web worker of any kind
+    pub fn is_worker(&self) -> bool {
+        matches!(
+            self,
+            Runtime::DedicatedWorker | Runtime::SharedWorker | Runtime::ServiceWorker
+        )
+    }
+
+    /// Check if the runtime provides Node.js APIs (`process`, `require`)
+    pub fn is_node_compatible(&self) -> bool {
+        matches!(
+            self,
+            Runtime::Node | Runtime::Bun | Runtime::Electron | Runtime::NwJs
+        )
+    }
+}
+
+impl std::fmt::Display for Runtime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Runtime::Browser => "browser",
+            Runtime::DedicatedWorker => "dedicated worker",
+            Runtime::SharedWorker => "shared worker",
+            Runtime::ServiceWorker => "service worker",
+            Runtime::Node => "Node.js",
+            Runtime::Deno => "Deno",
+            Runtime::Bun => "Bun",
+            Runtime::Electron => "Electron",
+            Runtime::NwJs => "NW.js",
+            Runtime::Unknown => "unknown",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Capabilities of the JavaScript runtime environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// `document` is available
+    pub dom: bool,
+    /// Node.js `process` object is available
+    pub process: bool,
+    /// `performance.now()` is available
+    pub performance: bool,
+    /// WebCrypto `crypto.subtle` is available
+    pub crypto_subtle: bool,
+    /// `isSecureContext` is `true` (always `false` in runtimes
+    /// that do not define `isSecureContext`, such as Node.js)
+    pub secure_context: bool,
+}
+
+thread_local! {
+    static RUNTIME: Runtime = detect_runtime();
+    static CAPABILITIES: Capabilities = detect_capabilities();
+}
+
+/// Runtime environment the code is executing in
+pub fn runtime() -> Runtime {
+    RUNTIME.with(|runtime| *runtime)
+}
+
+/// Capabilities of the runtime environment
+pub fn capabilities() -> Capabilities {
+    CAPABILITIES.with(|capabilities| *capabilities)
+}
+
+/// Check if `document` is available
+pub fn has_dom() -> bool {
+    capabilities().dom
+}
+
+/// Check if the code is executing in the browser main thread
+pub fn is_browser() -> bool {
+    runtime() == Runtime::Browser
+}
+
+/// Check if the code is executing in a web worker
+pub fn is_worker() -> bool {
+    runtime().is_worker()
+}
+
+/// Check if the code is executing in Node.js or a Node.js compatible runtime
+pub fn is_node() -> bool {
+    runtime().is_node_compatible()
+}
+
+/// Check if the global property `path` exists (is not `undefined`)
+fn defined(path: &str) -> bool {
+    resolve_global_path(path).is_ok()
+}
+
+/// Check if the global property `path` is an object
+fn is_object(path: &str) -> bool {
+    resolve_global_path(path)
+        .map(|value| value.is_object())
+        .unwrap_or(false)
+}
+
+fn detect_runtime() -> Runtime {
+    let global = js_sys::global();
+
+    if defined("Deno.version.deno") {
+        Runtime::Deno
+    } else if defined("Bun") || defined("process.versions.bun") {
+        Runtime::Bun
+    } else if defined("process.versions.electron") {
+        Runtime::Electron
+    } else if defined("process.versions.nw") || defined("nw") {
+        Runtime::NwJs
+    } else if defined("process.versions.node") {
+        Runtime::Node
+    } else if is_instance_of_global(&global, "ServiceWorkerGlobalScope") {
+        Runtime::ServiceWorker
+    } else if is_instance_of_global(&global, "SharedWorkerGlobalScope") {
+        Runtime::SharedWorker
+    } else if is_instance_of_global(&global, "DedicatedWorkerGlobalScope") {
+        Runtime::DedicatedWorker
+    } else if is_object("window") && is_object("document") {
+        Runtime::Browser
+    } else {
+        Runtime::Unknown
+    }
+}
+
+fn detect_capabilities() -> Capabilities {
+    Capabilities {
+        dom: is_object("document"),
+        process: is_object("process"),
+        performance: resolve_global_path("performance.now")
+            .map(|now| now.is_function())
+            .unwrap_or(false),
+        crypto_subtle: is_object("crypto.subtle"),
+        secure_context: resolve_global_path("isSecureContext")
+            .map(|value| value.as_bool() == Some(true))
+            .unwrap_or(false),
+    }
+}
@@ -0,0 +1,228 @@
+//!
+//! Crate-wide [`enum@Error`] type that wraps errors produced by all
+//! modules of this crate as well as JavaScript `Error` objects.
+//! [`enum@Error`] converts into a JavaScript `Error` instance, allowing
+//! it to be thrown or returned to JavaScript from `#[wasm_bindgen]` functions.
+//!
+
+use wasm_bindgen::prelude::*;
+use js_sys::Reflect;
+use thiserror::Error;
+use crate::abort::AbortError;
+use crate::callback::CallbackError;
+use crate::events::EventsError;
+use crate::init::InitError;
+use crate::options::OptionsError;
+use crate::path::PathError;
+use crate::promise::PromiseError;
+use crate::{buffer, timers, utils};
+
+/// Properties of a JavaScript `Error` object
+#[derive(Debug, Clone)]
+pub struct JsErrorInfo {
+    /// Error name (`Error`, `TypeError`, etc.)
+    pub name: String,
+    /// Error message
+    pub message: String,
+    /// Stack trace, if available
+    pub stack: Option<String>,
+    /// Error cause (`new Error(message, { cause })`), if available
+    pub cause: Option<JsValue>,
+    /// Original `Error` object (retaining its class and additional
+    /// properties), if the value was an `Error`
+    pub error: Option<js_sys::Error>,
+}
+
+impl std::fmt::Display for JsErrorInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.message)
+    }
+}
+
+impl From<JsValue> for JsErrorInfo {
+    /// Capture properties of a JavaScript `Error`. Strings are used as the
+    /// error message and any other value is preserved as the error cause.
+    fn from(value: JsValue) -> Self {
+        if let Some(err) = value.dyn_ref::<js_sys::Error>() {
+            JsErrorInfo {
+                name: String::from(err.name()),
+                message: String::from(err.message()),
+                stack: Reflect::get(&value, &JsValue::from("stack"))
+                    .ok()
+                    .and_then(|stack| stack.as_string()),
+                cause: Reflect::get(&value, &JsValue::from("cause"))
+                    .ok()
+                    .filter(|cause| !cause.is_undefined()),
+                error: Some(err.clone()),
+            }
+        } else if let Some(message) = value.as_string() {
+            JsErrorInfo {
+                name: "Error".to_string(),
+                message,
+                stack: None,
+                cause: None,
+                error: None,
+            }
+        } else {
+            JsErrorInfo {
+                name: "Error".to_string(),
+                message: crate::utils::describe(&value, 2),
+                stack: None,
+                cause: Some(value),
+                error: None,
+            }
+        }
+    }
+}
+
+impl From<&JsErrorInfo> for js_sys::Error {
+    /// Original `Error` object or, for other thrown values,
+    /// a new `Error` created from the captured properties
+    fn from(info: &JsErrorInfo) -> Self {
+        if let Some(err) = info.error.as_ref() {
+            return err.clone();
+        }
+
+        let err = js_sys::Error::new(&info.message);
+        err.set_name(&info.name);
+        if let Some(stack) = info.stack.as_ref() {
+            let _ = Reflect::set(&err, &JsValue::from("stack"), &JsValue::from(stack.as_str()));
+        }
+        if let Some(cause) = info.cause.as_ref() {
+            let _ = Reflect::set(&err, &JsValue::from("cause"), cause);
+        }
+        err
+    }
+}
+
+/// Crate-wide error type
+#[derive(Error, Debug)]
+pub enum Error {
+    /// JavaScript `Error` (or any other thrown value)
+    #[error("{0}")]
+    JsError(JsErrorInfo),
+
+    /// Custom error message
+    #[error("{0}")]
+    Custom(String),
+
+    #[error(transparent)]
+    Callback(#[from] CallbackError),
+
+    #[error(transparent)]
+    Timers(#[from] timers::Error),
+
+    #[error(transparent)]
+    Utils(#[from] utils::Error),
+
+    #[error(transparent)]
+    Options(#[from] OptionsError),
+
+    #[error(transparent)]
+    Buffer(#[from] buffer::Error),
+
+    #[error(transparent)]
+    Path(#[from] PathError),
+
+    #[error(transparent)]
+    Init(#[from] InitError),
+
+    #[error(transparent)]
+    Promise(#[from] PromiseError),
+
+    #[error(transparent)]
+    Abort(#[from] AbortError),
+
+    #[error(transparent)]
+    Events(#[from] EventsError),
+}
+
+impl Error {
+    /// Create a custom error
+    pub fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+
+    /// Name assigned to the JavaScript `Error` produced from this error
+    fn js_name(&self) -> &str {
+        match self {
+            Error::JsError(info) => &info.name,
+            Error::Custom(_) => "Error",
+            Error::Callback(_) => "CallbackError",
+            Error::Timers(_) => "TimerError",
+            Error::Utils(_) => "UtilsError",
+            Error::Options(_) => "OptionsError",
+            Error::Buffer(_) => "BufferError",
+            Error::Path(_) => "PathError",
+            Error::Init(_) => "InitError",
+            Error::Promise(_) => "PromiseError",
+            Error::Abort(_) => "AbortError",
+            Error::Events(_) => "EventsError",
+        }
+    }
+
+    /// JavaScript value wrapped by this error, used as the JavaScript error `cause`
+    fn js_cause(&self) -> Option<JsValue> {
+        match self {
+            Error::JsError(info) => info.cause.clone(),
+            Error::Callback(CallbackError::JsValue(v))
+            | Error::Timers(timers::Error::JsValue(v))
+            | Error::Utils(utils::Error::JsValue(v))
+            | Error::Options(OptionsError::JsValue(v))
+            | Error::Options(OptionsError::Property(utils::Error::JsValue(v)))
+            | Error::Buffer(buffer::Error::JsValue(v))
+            | Error::Init(InitError::JsValue(v))
+            | Error::Promise(PromiseError::JsValue(v))
+            | Error::Promise(PromiseError::Timers(timers::Error::JsValue(v)))
+            | Error::Abort(AbortError::JsValue(v))
+            | Error::Abort(AbortError::Aborted(v))
+            | Error::Events(EventsError::JsValue(v))
+            | Error::Buffer(buffer::Error::Element(utils::Error::JsValue(v))) => Some(v.clone()),
+            _ => None,
+        }
+    }
+
+    /// Create a JavaScript `Error` instance representing this error.
+    /// JavaScript `Error` objects (including subclasses such as `TypeError`
+    /// or `DOMException`) are returned as is; other thrown values produce
+    /// a new `Error` (see [`JsErrorInfo`]).
+    pub fn to_js_error(&self) -> js_sys::Error {
+        match self {
+            Error::JsError(info) => info.into(),
+            _ => {
+                let err = js_sys::Error::new(&self.to_string());
+                err.set_name(self.js_name());
+                if let Some(cause) = self.js_cause() {
+                    let _ = Reflect::set(&err, &JsValue::from("cause"), &cause);
+                }
+                err
+            }
+        }
+    }
+}
+
+impl From<JsValue> for Error {
+    fn from(value: JsValue) -> Self {
+        Error::JsError(JsErrorInfo::from(value))
+    }
+}
+
+impl From<String> for Error {
+    fn from(msg: String) -> Self {
+        Error::Custom(msg)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(msg: &str) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+impl From<Error> for JsValue {
+    fn from(err: Error) -> Self {
+        err.to_js_error().into()
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,337 @@
+//!
+//! Typed event emitter allowing Rust code to publish events to subscribers
+//! registered from Rust ([`EventEmitter::on()`]) and from JavaScript
+//! (via [`EventSubscriber`], which can be returned to JavaScript from
+//! `#[wasm_bindgen]` functions).
+//!
+//! ```ignore
+//! enum WalletEvent { Balance(u64), Disconnect }
+//!
+//! impl Event for WalletEvent {
+//!     fn event_name(&self) -> &str {
+//!         match self {
+//!             WalletEvent::Balance(_) => "balance",
+//!             WalletEvent::Disconnect => "disconnect",
+//!         }
+//!     }
+//!     fn to_js_value(&self) -> EventsResult<JsValue> {
+//!         match self {
+//!             WalletEvent::Balance(balance) => Ok(JsValue::from(*balance as f64)),
+//!             WalletEvent::Disconnect => Ok(JsValue::UNDEFINED),
+//!         }
+//!     }
+//! }
+//!
+//! let emitter = EventEmitter::<WalletEvent>::new();
+//! let id = emitter.on("balance", |balance: JsValue| { ... })?;
+//! emitter.emit(&WalletEvent::Balance(100))?;
+//! emitter.off(&id)?;
+//!
+//! // JavaScript: const token = subscriber.on("balance", (balance) => { ... });
+//! //             subscriber.off(token);
+//! let subscriber: EventSubscriber = emitter.subscriber();
+//! ```
+//!
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use js_sys::Function;
+use thiserror::Error;
+use crate::callback::{AsCallback, Callback, CallbackError, CallbackId, CallbackMap};
+
+/// Errors produced by the [`events`](self) module
+#[derive(Error, Debug)]
+pub enum EventsError {
+    /// Error contains a JsValue (including errors thrown by subscribers)
+    #[error("{}", crate::utils::describe(.0, 2))]
+    JsValue(JsValue),
+
+    #[error(transparent)]
+    Callback(#[from] CallbackError),
+
+    /// Event serialization failed at `path`
+    #[error("Unable to serialize event at `{path}`: {reason}")]
+    Serde {
+        path: String,
+        reason: String,
+    },
+}
+
+impl From<JsValue> for EventsError {
+    fn from(value: JsValue) -> Self {
+        EventsError::JsValue(value)
+    }
+}
+
+impl From<EventsError> for JsValue {
+    fn from(err: EventsError) -> Self {
+        crate::error::Error::from(err).into()
+    }
+}
+
+pub type EventsResult<T> = std::result::Result<T, EventsError>;
+
+/// Event published by an [`EventEmitter`]
+pub trait Event {
+    /// Name used to match subscribers of this event
+    fn event_name(&self) -> &str;
+
+    /// Event data delivered to subscribers
+    fn to_js_value(&self) -> EventsResult<JsValue>;
+}
+
+/// Untyped event carrying a JavaScript value
+#[derive(Debug, Clone)]
+pub struct JsEvent {
+    pub name: String,
+    pub data: JsValue,
+}
+
+impl JsEvent {
+    pub fn new(name: &str, data: JsValue) -> JsEvent {
+        JsEvent {
+            name: name.to_string(),
+            data,
+        }
+    }
+}
+
+impl Event for JsEvent {
+    fn event_name(&self) -> &str {
+        &self.name
+    }
+
+    fn to_js_value(&self) -> EventsResult<JsValue> {
+        Ok(self.data.clone())
+    }
+}
+
+/// Serialize a Rust value implementing [`serde::Serialize`] for use
+/// in [`Event::to_js_value()`]. Maps are serialized as plain objects.
+#[cfg(feature = "serde")]
+pub fn serialize<T>(value: &T) -> EventsResult<JsValue>
+where T: serde::Serialize + ?Sized
+{
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    serde_path_to_error::serialize(value, &serializer).map_err(|err| EventsError::Serde {
+        path: err.path().to_string(),
+        reason: err.inner().to_string(),
+    })
+}
+
+#[derive(Clone)]
+struct Listener {
+    id: CallbackId,
+    function: Function,
+    once: bool,
+}
+
+/// Subscribers shared by an [`EventEmitter`] and its [`EventSubscriber`]s
+struct Registry {
+    /// Callbacks created for Rust subscribers
+    callbacks: CallbackMap,
+    listeners: RefCell<HashMap<String, Vec<Listener>>>,
+}
+
+impl Registry {
+    fn add(&self, name: &str, id: CallbackId, function: Function, once: bool) {
+        self.listeners
+            .borrow_mut()
+            .entry(name.to_string())
+            .or_default()
+            .push(Listener { id, function, once });
+    }
+
+    fn subscribe<F>(&self, name: &str, handler: F, once: bool) -> EventsResult<CallbackId>
+    where F: FnMut(JsValue) + 'static
+    {
+        let callback: Callback<dyn FnMut(JsValue)> = Callback::new(handler);
+        let id = callback.get_id();
+        let function = AsRef::<Function>::as_ref(&callback).clone();
+        self.callbacks.insert(callback)?;
+        self.add(name, id, function, once);
+        Ok(id)
+    }
+
+    fn remove<P>(&self, predicate: P) -> EventsResult<bool>
+    where P: Fn(&CallbackId) -> bool
+    {
+        let mut removed = Vec::new();
+        self.listeners.borrow_mut().retain(|_, listeners| {
+            listeners.retain(|listener| {
+                let matches = predicate(&listener.id);
+                if matches {
+                    removed.push(listener.id);
+                }
+                !matches
+            });
+            !listeners.is_empty()
+        });
+
+        for id in removed.iter() {
+            self.callbacks.remove(id)?;
+        }
+        Ok(!removed.is_empty())
+    }
+
+    fn emit(&self, name: &str, data: &JsValue) -> EventsResult<usize> {
+        let listeners = match self.listeners.borrow_mut().get_mut(name) {
+            Some(listeners) => {
+                let snapshot = listeners.clone();
+                listeners.retain(|listener| !listener.once);
+                snapshot
+            }
+            None => return Ok(0),
+        };
+
+        // keep `once` callbacks alive until they have been invoked
+        let mut expired = Vec::new();
+        for listener in listeners.iter().filter(|listener| listener.once) {
+            expired.push(self.callbacks.remove(&listener.id)?);
+        }
+
+        let mut error = None;
+        for listener in listeners.iter() {
+            if let Err(err) = listener.function.call1(&JsValue::UNDEFINED, data) {
+                error.get_or_insert(err);
+            }
+        }
+
+        match error {
+            Some(err) => Err(EventsError::JsValue(err)),
+            None => Ok(listeners.len()),
+        }
+    }
+}
+
+///
+/// Event hub publishing events of type `E` to subscribers registered
+/// from Rust and from JavaScript (see [`EventSubscriber`]).
+/// Clones of the emitter share the same subscribers.
+///
+pub struct EventEmitter<E = JsEvent> {
+    registry: Rc<Registry>,
+    event: PhantomData<fn(&E)>,
+}
+
+impl<E> Clone for EventEmitter<E> {
+    fn clone(&self) -> Self {
+        EventEmitter {
+            registry: self.registry.clone(),
+            event: PhantomData,
+        }
+    }
+}
+
+impl<E> Default for EventEmitter<E> {
+    fn default() -> Self {
+        EventEmitter::new()
+    }
+}
+
+impl<E> std::fmt::Debug for EventEmitter<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EventEmitter{{...}}")
+    }
+}
+
+impl<E> EventEmitter<E> {
+    pub fn new() -> Self {
+        EventEmitter {
+            registry: Rc::new(Registry {
+                callbacks: CallbackMap::new(),
+                listeners: RefCell::new(HashMap::new()),
+            }),
+            event: PhantomData,
+        }
+    }
+
+    /// Subscribe `handler` to events named `name`
+    pub fn on<F>(&self, name: &str, handler: F) -> EventsResult<CallbackId>
+    where F: FnMut(JsValue) + 'static
+    {
+        self.registry.subscribe(name, handler, false)
+    }
+
+    /// Subscribe `handler` to the next event named `name`
+    pub fn once<F>(&self, name: &str, handler: F) -> EventsResult<CallbackId>
+    where F: FnMut(JsValue) + 'static
+    {
+        self.registry.subscribe(name, handler, true)
+    }
+
+    /// Unsubscribe the subscriber `id`. Returns `false` if it is not subscribed.
+    pub fn off(&self, id: &CallbackId) -> EventsResult<bool> {
+        self.registry.remove(|listener| listener == id)
+    }
+
+    /// Unsubscribe all subscribers
+    pub fn clear(&self) -> EventsResult<()> {
+        self.registry.remove(|_| true)?;
+        Ok(())
+    }
+
+    /// Number of subscribers of events named `name`
+    pub fn listener_count(&self, name: &str) -> usize {
+        self.registry
+            .listeners
+            .borrow()
+            .get(name)
+            .map(|listeners| listeners.len())
+            .unwrap_or(0)
+    }
+
+    /// Handle allowing JavaScript code to subscribe to events of this emitter
+    pub fn subscriber(&self) -> EventSubscriber {
+        EventSubscriber {
+            registry: self.registry.clone(),
+        }
+    }
+}
+
+impl<E> EventEmitter<E>
+where E: Event
+{
+    /// Deliver `event` to its subscribers. All subscribers are invoked even if
+    /// some of them throw, in which case the first error is returned.
+    /// Returns the number of subscribers invoked.
+    pub fn emit(&self, event: &E) -> EventsResult<usize> {
+        let data = event.to_js_value()?;
+        self.registry.emit(event.event_name(), &data)
+    }
+}
+
+///
+/// JavaScript interface of an [`EventEmitter`] (see [`EventEmitter::subscriber()`]).
+/// Subscription functions return a token (string representation of
+/// the subscriber's [`CallbackId`]) that is passed to `off()` to unsubscribe.
+///
+#[wasm_bindgen]
+pub struct EventSubscriber {
+    registry: Rc<Registry>,
+}
+
+#[wasm_bindgen]
+impl EventSubscriber {
+    /// Subscribe `callback` to events named `name`
+    pub fn on(&self, name: &str, callback: Function) -> String {
+        let id = CallbackId::new();
+        self.registry.add(name, id, callback, false);
+        id.to_string()
+    }
+
+    /// Subscribe `callback` to the next event named `name`
+    pub fn once(&self, name: &str, callback: Function) -> String {
+        let id = CallbackId::new();
+        self.registry.add(name, id, callback, true);
+        id.to_string()
+    }
+
+    /// Unsubscribe using the `token` returned by `on()` or `once()`
+    pub fn off(&self, token: &str) -> std::result::Result<bool, JsValue> {
+        Ok(self.registry.remove(|id| id.to_string() == token)?)
+    }
+}
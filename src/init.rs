@@ -1,27 +1,399 @@
 //!
 //! Workflow RS framework initializers
-//! 
+//!
+//! The framework registry is stored in the JavaScript global object under
+//! the `$workflow$` property and contains the `workflow` object as well as
+//! `modules` - a collection of JavaScript modules (libraries) supplied by the
+//! host page via [`init_workflow()`], registered using [`register_module()`]
+//! or loaded on demand using [`load_module()`].
+//!
+//! Independently built WASM bundles running on the same page share the
+//! default registry. To isolate a bundle, select a namespace using
+//! [`InitBuilder`] or by setting the `WORKFLOW_WASM_NAMESPACE` environment
+//! variable at build time. A namespaced registry is stored under the
+//! `$workflow:<namespace>$` property and all registries are listed in the
+//! `$workflow-registries$` global object (see [`registries()`]).
+//!
+//! ```ignore
+//! InitBuilder::new()
+//!     .namespace("wallet")
+//!     .modules(&modules)
+//!     .init()?;
+//! ```
+//!
 
 use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use std::cell::{Cell, RefCell};
+use js_sys::{Object, Promise, Reflect};
+use thiserror::Error;
+use crate::env::{self, Runtime};
+
+/// Name of the global property holding the default framework registry
+const GLOBAL_KEY: &str = "$workflow$";
+
+/// Name of the global property listing registries of all namespaces
+const REGISTRIES_KEY: &str = "$workflow-registries$";
+
+/// Name of the default namespace
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(msg: &str);
+}
+
+#[wasm_bindgen(inline_js = "export function dynamic_import(s) { return import(s); }")]
+extern "C" {
+    /// Start a dynamic `import()` of `specifier`
+    #[wasm_bindgen(catch)]
+    fn dynamic_import(specifier: &str) -> std::result::Result<Promise, JsValue>;
+}
+
+thread_local! {
+    static NAMESPACE: RefCell<String> = RefCell::new(
+        option_env!("WORKFLOW_WASM_NAMESPACE")
+            .filter(|namespace| !namespace.is_empty())
+            .unwrap_or(DEFAULT_NAMESPACE)
+            .to_string()
+    );
+    /// Token identifying registries created by this WASM instance
+    static OWNER: String = format!("{:016x}", (js_sys::Math::random() * u64::MAX as f64) as u64);
+    static COLLISION_REPORTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Errors produced by the [`init`](self) module
+#[derive(Error, Debug)]
+pub enum InitError {
+    /// Error contains a JsValue
+    #[error("{}", crate::utils::describe(.0, 2))]
+    JsValue(JsValue),
+
+    /// The framework registry has not been created
+    #[error("Workflow is not initialized, please call `init_workflow()`")]
+    NotInitialized,
+
+    /// Requested module is not registered
+    #[error("Module `{0}` is not registered")]
+    ModuleMissing(String),
+
+    /// Dynamic `import()` of a module failed
+    #[error("Unable to load module `{name}` from `{specifier}`: {reason}")]
+    LoadFailed {
+        name: String,
+        specifier: String,
+        reason: String,
+    },
+}
+
+impl From<JsValue> for InitError {
+    fn from(value: JsValue) -> Self {
+        InitError::JsValue(value)
+    }
+}
+
+impl From<InitError> for JsValue {
+    fn from(err: InitError) -> Self {
+        crate::error::Error::from(err).into()
+    }
+}
 
+pub type InitResult<T> = std::result::Result<T, InitError>;
+
+/// Initialize the framework registry with the `workflow` object and the `modules`
+/// collection. Repeated calls merge `modules` into the existing registry and
+/// replace `workflow` only if a value is supplied.
 #[wasm_bindgen(js_name="init_workflow")]
 pub fn init_workflow(workflow: &JsValue, modules: &JsValue) -> std::result::Result<(), JsValue> {
-    let global = js_sys::Object::new();
-    js_sys::Reflect::set(&js_sys::global(), &"$workflow$".into(), &global)?;
-    js_sys::Reflect::set(&global,&"workflow".into(),&workflow)?;
-    js_sys::Reflect::set(&global,&"modules".into(), &modules)?;
+    Ok(init_registry(workflow, modules)?)
+}
+
+/// Initialize the registry of the current namespace (see [`init_workflow()`])
+fn init_registry(workflow: &JsValue, modules: &JsValue) -> InitResult<()> {
+    let global = ensure_global()?;
+    if !workflow.is_undefined() && !workflow.is_null() {
+        Reflect::set(&global, &"workflow".into(), workflow)?;
+    }
+    if modules.is_object() {
+        let registry = modules_object()?;
+        Object::assign(&registry, modules.unchecked_ref::<Object>());
+    }
     Ok(())
 }
 
-pub fn global() -> std::result::Result<JsValue,JsValue> {
-    Ok(js_sys::Reflect::get(&js_sys::global(), &"$workflow$".into())?)
+/// Select the registry `namespace` and initialize it (see [`init_workflow()`])
+#[wasm_bindgen(js_name="init_workflow_namespace")]
+pub fn init_workflow_namespace(namespace: &str, workflow: &JsValue, modules: &JsValue) -> std::result::Result<(), JsValue> {
+    set_namespace(namespace);
+    init_workflow(workflow, modules)
 }
 
-pub fn workflow() -> std::result::Result<JsValue,JsValue> {
-    Ok(js_sys::Reflect::get(&global()?, &"workflow".into())?)
+/// Builder for initialization of a namespaced framework registry
+#[derive(Debug, Clone)]
+pub struct InitBuilder {
+    namespace: Option<String>,
+    workflow: JsValue,
+    modules: JsValue,
 }
 
-pub fn modules() -> std::result::Result<JsValue,JsValue> {
-    Ok(js_sys::Reflect::get(&global()?, &"modules".into())?)
+impl Default for InitBuilder {
+    fn default() -> Self {
+        InitBuilder::new()
+    }
 }
 
+impl InitBuilder {
+    pub fn new() -> InitBuilder {
+        InitBuilder {
+            namespace: None,
+            workflow: JsValue::UNDEFINED,
+            modules: JsValue::UNDEFINED,
+        }
+    }
+
+    /// Namespace of the registry (the current [`namespace()`] if not set)
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(namespace.to_string());
+        self
+    }
+
+    /// `workflow` object to store in the registry
+    pub fn workflow(mut self, workflow: &JsValue) -> Self {
+        self.workflow = workflow.clone();
+        self
+    }
+
+    /// Modules to merge into the registry
+    pub fn modules(mut self, modules: &JsValue) -> Self {
+        self.modules = modules.clone();
+        self
+    }
+
+    /// Select the namespace and initialize the registry
+    pub fn init(self) -> InitResult<()> {
+        if let Some(namespace) = self.namespace.as_ref() {
+            set_namespace(namespace);
+        }
+        init_registry(&self.workflow, &self.modules)
+    }
+}
+
+/// Select the registry namespace used by this WASM instance.
+/// An empty `namespace` selects [`DEFAULT_NAMESPACE`].
+pub fn set_namespace(namespace: &str) {
+    let namespace = if namespace.is_empty() {
+        DEFAULT_NAMESPACE
+    } else {
+        namespace
+    };
+    NAMESPACE.with(|current| *current.borrow_mut() = namespace.to_string());
+}
+
+/// Registry namespace used by this WASM instance
+pub fn namespace() -> String {
+    NAMESPACE.with(|namespace| namespace.borrow().clone())
+}
+
+/// Name of the global property holding the registry of `namespace`
+fn global_key(namespace: &str) -> String {
+    if namespace == DEFAULT_NAMESPACE {
+        GLOBAL_KEY.to_string()
+    } else {
+        format!("$workflow:{namespace}$")
+    }
+}
+
+/// Obtain the object listing registries of all namespaces, creating it if missing
+fn registries_object() -> InitResult<Object> {
+    let registries = Reflect::get(&js_sys::global(), &REGISTRIES_KEY.into())?;
+    if registries.is_object() {
+        return Ok(registries.unchecked_into());
+    }
+
+    let registries = Object::new();
+    Reflect::set(&js_sys::global(), &REGISTRIES_KEY.into(), &registries)?;
+    Ok(registries)
+}
+
+/// Obtain the registry object, creating it if it does not exist.
+/// Warns (once) if the registry was created by another WASM instance.
+fn ensure_global() -> InitResult<JsValue> {
+    let namespace = namespace();
+    let key = JsValue::from(global_key(&namespace));
+    let owner = OWNER.with(|owner| JsValue::from(owner.as_str()));
+
+    let global = Reflect::get(&js_sys::global(), &key)?;
+    if global.is_object() {
+        let existing = Reflect::get(&global, &"owner".into())?;
+        if existing.is_string() && existing != owner && !COLLISION_REPORTED.with(|reported| reported.replace(true)) {
+            console_warn(&format!(
+                "workflow: registry namespace `{namespace}` is shared with another WASM instance; \
+                use `InitBuilder::namespace()` or `init_workflow_namespace()` to isolate instances"
+            ));
+        }
+        return Ok(global);
+    }
+
+    let global = JsValue::from(Object::new());
+    Reflect::set(&global, &"modules".into(), &Object::new())?;
+    Reflect::set(&global, &"namespace".into(), &JsValue::from(namespace.as_str()))?;
+    Reflect::set(&global, &"owner".into(), &owner)?;
+    Reflect::set(&js_sys::global(), &key, &global)?;
+    let registries = registries_object()?;
+    Reflect::set(&registries, &JsValue::from(namespace.as_str()), &global)?;
+    Ok(global)
+}
+
+/// Obtain the `modules` object of an existing registry, creating it if missing
+fn modules_object() -> InitResult<Object> {
+    let modules = Reflect::get(&global()?, &"modules".into())?;
+    if modules.is_object() {
+        return Ok(modules.unchecked_into());
+    }
+
+    let modules = Object::new();
+    Reflect::set(&global()?, &"modules".into(), &modules)?;
+    Ok(modules)
+}
+
+/// Obtain the framework registry object of the current [`namespace()`].
+/// Returns [`InitError::NotInitialized`] if the registry does not exist.
+pub fn global() -> InitResult<JsValue> {
+    registry(&namespace())
+}
+
+/// Obtain the framework registry object of `namespace`, which may
+/// belong to another WASM instance.
+/// Returns [`InitError::NotInitialized`] if the registry does not exist.
+pub fn registry(namespace: &str) -> InitResult<JsValue> {
+    let global = Reflect::get(&js_sys::global(), &global_key(namespace).into())?;
+    if global.is_object() {
+        Ok(global)
+    } else {
+        Err(InitError::NotInitialized)
+    }
+}
+
+/// Namespaces and registry objects of all WASM instances on the page
+/// (including registries created by the host page under `$workflow$`).
+pub fn registries() -> InitResult<Vec<(String, JsValue)>> {
+    let mut list = Vec::new();
+    let registries = Reflect::get(&js_sys::global(), &REGISTRIES_KEY.into())?;
+    if registries.is_object() {
+        for namespace in Object::keys(registries.unchecked_ref::<Object>()).iter() {
+            if let Some(name) = namespace.as_string() {
+                list.push((name, Reflect::get(&registries, &namespace)?));
+            }
+        }
+    }
+
+    if !list.iter().any(|(name, _)| name == DEFAULT_NAMESPACE) {
+        if let Ok(global) = registry(DEFAULT_NAMESPACE) {
+            list.push((DEFAULT_NAMESPACE.to_string(), global));
+        }
+    }
+    Ok(list)
+}
+
+/// Obtain the `workflow` object supplied to [`init_workflow()`]
+/// (`undefined` if it was not supplied).
+pub fn workflow() -> InitResult<JsValue> {
+    Ok(Reflect::get(&global()?, &"workflow".into())?)
+}
+
+/// Obtain the `modules` object containing all registered modules.
+pub fn modules() -> InitResult<JsValue> {
+    Ok(modules_object()?.into())
+}
+
+/// Register `value` as the module `name`, creating the registry if needed.
+/// An existing module with the same name is replaced.
+pub fn register_module(name: &str, value: &JsValue) -> InitResult<()> {
+    ensure_global()?;
+    let registry = modules_object()?;
+    Reflect::set(&registry, &name.into(), value)?;
+    Ok(())
+}
+
+/// Obtain the module registered under `name`.
+pub fn module(name: &str) -> InitResult<JsValue> {
+    let registry = modules_object()?;
+    let module = Reflect::get(&registry, &name.into())?;
+    if module.is_undefined() {
+        Err(InitError::ModuleMissing(name.to_string()))
+    } else {
+        Ok(module)
+    }
+}
+
+/// Check if the module `name` is registered.
+pub fn has_module(name: &str) -> bool {
+    module(name).is_ok()
+}
+
+/// Names of all registered modules.
+pub fn module_names() -> InitResult<Vec<String>> {
+    Ok(Object::keys(&modules_object()?)
+        .iter()
+        .filter_map(|name| name.as_string())
+        .collect())
+}
+
+/// Obtain the `loading` object tracking pending [`load_module()`] imports
+fn loading_object() -> InitResult<Object> {
+    let global = ensure_global()?;
+    let loading = Reflect::get(&global, &"loading".into())?;
+    if loading.is_object() {
+        return Ok(loading.unchecked_into());
+    }
+
+    let loading = Object::new();
+    Reflect::set(&global, &"loading".into(), &loading)?;
+    Ok(loading)
+}
+
+///
+/// Load the module `name` using dynamic `import(url_or_specifier)` and
+/// register it in the `modules` registry. If the module is already registered,
+/// it is returned without loading. Concurrent calls for the same module
+/// share a single pending import.
+///
+/// Note: dynamic import is not available in service workers.
+///
+pub async fn load_module(name: &str, url_or_specifier: &str) -> InitResult<JsValue> {
+    if let Ok(module) = module(name) {
+        return Ok(module);
+    }
+
+    if env::runtime() == Runtime::ServiceWorker {
+        return Err(InitError::LoadFailed {
+            name: name.to_string(),
+            specifier: url_or_specifier.to_string(),
+            reason: "dynamic import() is not supported in service workers".to_string(),
+        });
+    }
+
+    let failed = |err: JsValue| InitError::LoadFailed {
+        name: name.to_string(),
+        specifier: url_or_specifier.to_string(),
+        reason: crate::utils::describe(&err, 1),
+    };
+
+    let loading = loading_object()?;
+    let key = JsValue::from(name);
+    let pending = Reflect::get(&loading, &key)?;
+    let promise = if pending.is_instance_of::<Promise>() {
+        pending.unchecked_into::<Promise>()
+    } else {
+        let promise = dynamic_import(url_or_specifier).map_err(failed)?;
+        Reflect::set(&loading, &key, &promise)?;
+        promise
+    };
+
+    let result = JsFuture::from(promise).await;
+    Reflect::delete_property(&loading, &key)?;
+    let module = result.map_err(failed)?;
+    register_module(name, &module)?;
+    Ok(module)
+}
@@ -7,10 +7,18 @@
 //! 
 //! 
 
+pub mod error;
 pub mod timers;
+pub mod buffer;
 pub mod utils;
 pub mod callback;
 pub mod panic;
 pub mod init;
+pub mod env;
+pub mod log;
+pub mod promise;
+pub mod abort;
+pub mod events;
 pub mod options;
+pub mod path;
 pub mod prelude;
@@ -0,0 +1,384 @@
+//!
+//! Logging to the JavaScript console. Messages are logged using the
+//! [`log_trace!`](crate::log_trace), [`log_debug!`](crate::log_debug),
+//! [`log_info!`](crate::log_info), [`log_warn!`](crate::log_warn) and
+//! [`log_error!`](crate::log_error) macros, filtered by a level that can be
+//! adjusted at runtime for each target (module path) and retained in a ring
+//! buffer that is included in panic reports (see [`crate::panic`]).
+//!
+//! ```ignore
+//! log::set_target_level("my_crate::rpc", LevelFilter::Trace);
+//!
+//! let _group = log::group("connecting");
+//! log_info!("connecting to {}", url);
+//! log_warn!(target: "rpc", "retrying in {}ms", delay);
+//! ```
+//!
+//! With the `log` feature enabled, `init_log_backend()` routes records
+//! of the [`log`](https://docs.rs/log) crate through this module.
+//!
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+
+/// Default capacity of the log ring buffer
+pub const DEFAULT_BUFFER_CAPACITY: usize = 128;
+
+/// Log message level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Most verbose [`Level`] enabled for a target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LevelFilter {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LevelFilter {
+    /// Check if messages of `level` pass this filter
+    pub fn enabled(&self, level: Level) -> bool {
+        *self as usize >= level as usize
+    }
+}
+
+/// Log message retained in the ring buffer
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    /// Time of the message (milliseconds since UNIX epoch)
+    pub timestamp: f64,
+}
+
+impl std::fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}: {}", self.level, self.target, self.message)
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = debug)]
+    fn console_debug(msg: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = info)]
+    fn console_info(msg: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(msg: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(msg: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = group)]
+    fn console_group(label: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = groupCollapsed)]
+    fn console_group_collapsed(label: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = groupEnd)]
+    fn console_group_end();
+}
+
+struct Logger {
+    level: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+    buffer: VecDeque<LogRecord>,
+    capacity: usize,
+}
+
+thread_local! {
+    static LOGGER: RefCell<Logger> = const {
+        RefCell::new(Logger {
+            level: LevelFilter::Info,
+            targets: Vec::new(),
+            buffer: VecDeque::new(),
+            capacity: DEFAULT_BUFFER_CAPACITY,
+        })
+    };
+}
+
+/// Set the level of targets without a level of their own (`Info` by default)
+pub fn set_level(level: LevelFilter) {
+    LOGGER.with(|logger| logger.borrow_mut().level = level);
+}
+
+/// Set the level of `target` and its sub-targets (`target::*`)
+pub fn set_target_level(target: &str, level: LevelFilter) {
+    LOGGER.with(|logger| {
+        let mut logger = logger.borrow_mut();
+        logger.targets.retain(|(name, _)| name != target);
+        logger.targets.push((target.to_string(), level));
+    });
+}
+
+/// Remove the level set for `target` using [`set_target_level()`]
+pub fn clear_target_level(target: &str) {
+    LOGGER.with(|logger| logger.borrow_mut().targets.retain(|(name, _)| name != target));
+}
+
+/// Level in effect for `target`: the level of the most specific
+/// matching target or the level set using [`set_level()`]
+pub fn level_for(target: &str) -> LevelFilter {
+    LOGGER.with(|logger| {
+        let logger = logger.borrow();
+        logger
+            .targets
+            .iter()
+            .filter(|(name, _)| {
+                target == name
+                    || (target.starts_with(name.as_str()) && target[name.len()..].starts_with("::"))
+            })
+            .max_by_key(|(name, _)| name.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(logger.level)
+    })
+}
+
+/// Check if messages of `level` are enabled for `target`
+pub fn enabled(level: Level, target: &str) -> bool {
+    level_for(target).enabled(level)
+}
+
+/// Log a message (used by the `log_*!` macros)
+pub fn log(level: Level, target: &str, args: std::fmt::Arguments) {
+    if !enabled(level, target) {
+        return;
+    }
+
+    let message = args.to_string();
+    match level {
+        Level::Error => console_error(&message),
+        Level::Warn => console_warn(&message),
+        Level::Info => console_info(&message),
+        Level::Debug | Level::Trace => console_debug(&message),
+    }
+
+    LOGGER.with(|logger| {
+        if let Ok(mut logger) = logger.try_borrow_mut() {
+            if logger.capacity == 0 {
+                return;
+            }
+            while logger.buffer.len() >= logger.capacity {
+                logger.buffer.pop_front();
+            }
+            logger.buffer.push_back(LogRecord {
+                level,
+                target: target.to_string(),
+                message,
+                timestamp: js_sys::Date::now(),
+            });
+        }
+    });
+}
+
+/// Set the number of messages retained in the ring buffer
+/// (`0` disables the buffer)
+pub fn set_buffer_capacity(capacity: usize) {
+    LOGGER.with(|logger| {
+        let mut logger = logger.borrow_mut();
+        logger.capacity = capacity;
+        while logger.buffer.len() > capacity {
+            logger.buffer.pop_front();
+        }
+    });
+}
+
+/// Messages retained in the ring buffer, oldest first
+pub fn buffered() -> Vec<LogRecord> {
+    LOGGER.with(|logger| {
+        logger
+            .try_borrow()
+            .map(|logger| logger.buffer.iter().cloned().collect())
+            .unwrap_or_default()
+    })
+}
+
+/// Remove all messages from the ring buffer
+pub fn clear_buffer() {
+    LOGGER.with(|logger| logger.borrow_mut().buffer.clear());
+}
+
+/// Console group handle, dropping which closes the group (`console.groupEnd()`)
+#[must_use = "the console group is closed when the handle is dropped"]
+pub struct GroupHandle(());
+
+impl Drop for GroupHandle {
+    fn drop(&mut self) {
+        console_group_end();
+    }
+}
+
+/// Open a console group (`console.group()`)
+pub fn group(label: &str) -> GroupHandle {
+    console_group(label);
+    GroupHandle(())
+}
+
+/// Open a collapsed console group (`console.groupCollapsed()`)
+pub fn group_collapsed(label: &str) -> GroupHandle {
+    console_group_collapsed(label);
+    GroupHandle(())
+}
+
+/// Log a message of level [`Level::Trace`](crate::log::Level::Trace)
+#[macro_export]
+macro_rules! log_trace {
+    (target: $target:expr, $($arg:tt)+) => (
+        $crate::log::log($crate::log::Level::Trace, $target, format_args!($($arg)+))
+    );
+    ($($arg:tt)+) => (
+        $crate::log::log($crate::log::Level::Trace, module_path!(), format_args!($($arg)+))
+    );
+}
+
+/// Log a message of level [`Level::Debug`](crate::log::Level::Debug)
+#[macro_export]
+macro_rules! log_debug {
+    (target: $target:expr, $($arg:tt)+) => (
+        $crate::log::log($crate::log::Level::Debug, $target, format_args!($($arg)+))
+    );
+    ($($arg:tt)+) => (
+        $crate::log::log($crate::log::Level::Debug, module_path!(), format_args!($($arg)+))
+    );
+}
+
+/// Log a message of level [`Level::Info`](crate::log::Level::Info)
+#[macro_export]
+macro_rules! log_info {
+    (target: $target:expr, $($arg:tt)+) => (
+        $crate::log::log($crate::log::Level::Info, $target, format_args!($($arg)+))
+    );
+    ($($arg:tt)+) => (
+        $crate::log::log($crate::log::Level::Info, module_path!(), format_args!($($arg)+))
+    );
+}
+
+/// Log a message of level [`Level::Warn`](crate::log::Level::Warn)
+#[macro_export]
+macro_rules! log_warn {
+    (target: $target:expr, $($arg:tt)+) => (
+        $crate::log::log($crate::log::Level::Warn, $target, format_args!($($arg)+))
+    );
+    ($($arg:tt)+) => (
+        $crate::log::log($crate::log::Level::Warn, module_path!(), format_args!($($arg)+))
+    );
+}
+
+/// Log a message of level [`Level::Error`](crate::log::Level::Error)
+#[macro_export]
+macro_rules! log_error {
+    (target: $target:expr, $($arg:tt)+) => (
+        $crate::log::log($crate::log::Level::Error, $target, format_args!($($arg)+))
+    );
+    ($($arg:tt)+) => (
+        $crate::log::log($crate::log::Level::Error, module_path!(), format_args!($($arg)+))
+    );
+}
+
+#[cfg(feature = "log")]
+mod backend {
+    use super::Level;
+
+    /// [`log::Log`] implementation forwarding records to [`crate::log`]
+    pub struct ConsoleLogger;
+
+    impl From<::log::Level> for Level {
+        fn from(level: ::log::Level) -> Self {
+            match level {
+                ::log::Level::Error => Level::Error,
+                ::log::Level::Warn => Level::Warn,
+                ::log::Level::Info => Level::Info,
+                ::log::Level::Debug => Level::Debug,
+                ::log::Level::Trace => Level::Trace,
+            }
+        }
+    }
+
+    impl ::log::Log for ConsoleLogger {
+        fn enabled(&self, metadata: &::log::Metadata) -> bool {
+            super::enabled(metadata.level().into(), metadata.target())
+        }
+
+        fn log(&self, record: &::log::Record) {
+            super::log(record.level().into(), record.target(), *record.args());
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: ConsoleLogger = ConsoleLogger;
+
+    /// Install [`ConsoleLogger`] as the [`log`](https://docs.rs/log) crate backend.
+    /// Levels are controlled using [`set_level()`](super::set_level) and
+    /// [`set_target_level()`](super::set_target_level).
+    pub fn init_log_backend() -> Result<(), ::log::SetLoggerError> {
+        ::log::set_logger(&LOGGER)?;
+        ::log::set_max_level(::log::LevelFilter::Trace);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "log")]
+pub use backend::{init_log_backend, ConsoleLogger};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_filter_ordering() {
+        assert!(!LevelFilter::Off.enabled(Level::Error));
+        assert!(LevelFilter::Error.enabled(Level::Error));
+        assert!(!LevelFilter::Error.enabled(Level::Warn));
+        assert!(LevelFilter::Info.enabled(Level::Warn));
+        assert!(LevelFilter::Info.enabled(Level::Info));
+        assert!(!LevelFilter::Info.enabled(Level::Debug));
+        assert!(LevelFilter::Trace.enabled(Level::Trace));
+        assert!(LevelFilter::Trace.enabled(Level::Error));
+    }
+
+    #[test]
+    fn level_for_matches_most_specific_target() {
+        set_level(LevelFilter::Warn);
+        set_target_level("a", LevelFilter::Info);
+        set_target_level("a::b", LevelFilter::Trace);
+        set_target_level("a::b::c", LevelFilter::Off);
+
+        assert_eq!(level_for("a"), LevelFilter::Info);
+        assert_eq!(level_for("a::x"), LevelFilter::Info);
+        assert_eq!(level_for("a::b"), LevelFilter::Trace);
+        assert_eq!(level_for("a::b::d"), LevelFilter::Trace);
+        assert_eq!(level_for("a::b::c"), LevelFilter::Off);
+        assert_eq!(level_for("a::b::c::d"), LevelFilter::Off);
+
+        // `a::b` must not match `a::bc`
+        assert_eq!(level_for("a::bc"), LevelFilter::Info);
+        assert_eq!(level_for("ab"), LevelFilter::Warn);
+        assert_eq!(level_for("other"), LevelFilter::Warn);
+
+        clear_target_level("a::b");
+        assert_eq!(level_for("a::b::d"), LevelFilter::Info);
+        assert_eq!(level_for("a::b::c"), LevelFilter::Off);
+    }
+}
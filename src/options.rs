@@ -1,5 +1,5 @@
 //! 
-//! Helper trait for managing options struct which extends [Object](js_sys::Object)
+//! Helper trait for managing options struct which extends [`Object`]
 //! ```
 //! // create MyOptions struct
 //!
@@ -33,9 +33,311 @@
 //! 
 //! ```
 //! 
+//! With the `serde` feature enabled, options can be created from and converted
+//! into Rust structs using `OptionsExt::from_serde()` and `OptionsExt::to_serde()`.
+//! 
 
 use wasm_bindgen::prelude::*;
-use js_sys::Object;
+use js_sys::{Array, Object, Reflect};
+use thiserror::Error;
+use crate::path::{Path, PathError, Segment};
+use crate::utils::{self, type_name, FromJsProp};
+
+/// Errors produced by the [`options`](self) module
+#[derive(Error, Debug)]
+pub enum OptionsError {
+    /// Error contains a JsValue
+    #[error("{}", crate::utils::describe(.0, 2))]
+    JsValue(JsValue),
+
+    /// Property path could not be parsed
+    #[error(transparent)]
+    Path(#[from] PathError),
+
+    /// Property at the given path is not defined
+    #[error("Property `{0}` is not defined")]
+    Missing(String),
+
+    /// A segment of the path resolves to a value that is not an object
+    /// while reading `key` from it
+    #[error("`{path}` is {found}, cannot get `{key}`")]
+    NotAnObject {
+        path: String,
+        found: String,
+        key: String,
+    },
+
+    /// A segment of the path resolves to a value that is not an object
+    /// while setting `key` on it
+    #[error("`{path}` is {found}, cannot set `{key}`")]
+    CannotSet {
+        path: String,
+        found: String,
+        key: String,
+    },
+
+    /// Property value could not be converted to the requested type
+    /// (see [`FromJsProp`])
+    #[error(transparent)]
+    Property(#[from] utils::Error),
+
+    /// Value could not be converted to the requested type
+    #[error("Unable to convert property `{path}`: {reason}")]
+    Conversion {
+        path: String,
+        reason: String,
+    },
+
+    /// Serde (de)serialization failure at the given field path
+    #[error("Serde error at `{path}`: {reason}")]
+    Serde {
+        path: String,
+        reason: String,
+    },
+
+    /// Options do not conform to a [`Schema`]; contains all violations found
+    #[error("Invalid options: {}", violations_to_string(.0))]
+    Validation(Vec<Violation>),
+}
+
+impl From<JsValue> for OptionsError {
+    fn from(value: JsValue) -> Self {
+        OptionsError::JsValue(value)
+    }
+}
+
+impl From<OptionsError> for JsValue {
+    fn from(err: OptionsError) -> Self {
+        crate::error::Error::from(err).into()
+    }
+}
+
+pub type OptionsResult<T> = std::result::Result<T, OptionsError>;
+
+/// Expected JavaScript type of a [`Schema`] property
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaType {
+    /// Any defined value
+    Any,
+    Bool,
+    Number,
+    BigInt,
+    String,
+    Array,
+    /// Object that is not an array
+    Object,
+    Function,
+    /// Instance of a globally accessible class (see [`utils::is_instance_of_global`])
+    Instance(&'static str),
+}
+
+impl SchemaType {
+    /// Check if the value is of this type
+    pub fn matches(&self, value: &JsValue) -> bool {
+        match self {
+            SchemaType::Any => !value.is_undefined(),
+            SchemaType::Bool => value.as_bool().is_some(),
+            SchemaType::Number => value.as_f64().is_some(),
+            SchemaType::BigInt => value.is_bigint(),
+            SchemaType::String => value.is_string(),
+            SchemaType::Array => Array::is_array(value),
+            SchemaType::Object => value.is_object() && !Array::is_array(value),
+            SchemaType::Function => value.is_function(),
+            SchemaType::Instance(name) => utils::is_instance_of_global(value, name),
+        }
+    }
+}
+
+impl std::fmt::Display for SchemaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SchemaType::Any => "any value",
+            SchemaType::Bool => "a boolean",
+            SchemaType::Number => "a number",
+            SchemaType::BigInt => "a bigint",
+            SchemaType::String => "a string",
+            SchemaType::Array => "an array",
+            SchemaType::Object => "an object",
+            SchemaType::Function => "a function",
+            SchemaType::Instance(name) => return write!(f, "an instance of {name}"),
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Description of a single [`Schema`] property
+#[derive(Debug, Clone)]
+pub struct SchemaField {
+    pub key: String,
+    pub kind: SchemaType,
+    pub required: bool,
+    pub default: Option<JsValue>,
+    pub schema: Option<Schema>,
+}
+
+///
+/// Description of the expected shape of an options object.
+/// `undefined` and `null` values are treated as absent.
+///
+/// ```ignore
+/// let schema = Schema::new()
+///     .required("url", SchemaType::String)
+///     .with_default("timeout", SchemaType::Number, JsValue::from(3000))
+///     .nested("retry", false, Schema::new()
+///         .with_default("attempts", SchemaType::Number, JsValue::from(3))
+///     );
+///
+/// options.validate(&schema)?;
+/// ```
+///
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    fields: Vec<SchemaField>,
+}
+
+impl Schema {
+    /// Create an empty [`Schema`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a property that must be present
+    pub fn required(self, key: &str, kind: SchemaType) -> Self {
+        self.field(key, kind, true, None, None)
+    }
+
+    /// Add a property that may be absent
+    pub fn optional(self, key: &str, kind: SchemaType) -> Self {
+        self.field(key, kind, false, None, None)
+    }
+
+    /// Add an optional property that receives `default` when absent.
+    /// Array and plain object defaults are copied into each options
+    /// object, other objects (functions, class instances) are shared.
+    pub fn with_default(self, key: &str, kind: SchemaType, default: JsValue) -> Self {
+        self.field(key, kind, false, Some(default), None)
+    }
+
+    /// Add an object property described by a nested `schema`
+    pub fn nested(self, key: &str, required: bool, schema: Schema) -> Self {
+        self.field(key, SchemaType::Object, required, None, Some(schema))
+    }
+
+    fn field(mut self, key: &str, kind: SchemaType, required: bool, default: Option<JsValue>, schema: Option<Schema>) -> Self {
+        self.fields.push(SchemaField {
+            key: key.to_string(),
+            kind,
+            required,
+            default,
+            schema,
+        });
+        self
+    }
+
+    /// Schema properties
+    pub fn fields(&self) -> &[SchemaField] {
+        &self.fields
+    }
+
+    fn has_defaults(&self) -> bool {
+        self.fields.iter().any(|field| {
+            field.default.is_some() || field.schema.as_ref().map(|schema| schema.has_defaults()).unwrap_or(false)
+        })
+    }
+
+    /// Collect all violations of this schema found in `target`
+    pub fn violations(&self, target: &JsValue) -> OptionsResult<Vec<Violation>> {
+        let mut violations = Vec::new();
+        self.collect_violations(target, "", &mut violations)?;
+        Ok(violations)
+    }
+
+    fn collect_violations(&self, target: &JsValue, prefix: &str, violations: &mut Vec<Violation>) -> OptionsResult<()> {
+        for field in self.fields.iter() {
+            let path = format!("{prefix}{}", field.key);
+            let value = Reflect::get(target, &JsValue::from(field.key.as_str()))?;
+            if value.is_undefined() || value.is_null() {
+                if field.required {
+                    violations.push(Violation::Missing { path });
+                }
+            } else if !field.kind.matches(&value) {
+                violations.push(Violation::Type {
+                    path,
+                    expected: field.kind,
+                    found: type_name(&value),
+                });
+            } else if let Some(schema) = field.schema.as_ref() {
+                schema.collect_violations(&value, &format!("{path}."), violations)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set absent properties of `target` to their default values
+    pub fn apply_defaults(&self, target: &JsValue) -> OptionsResult<()> {
+        for field in self.fields.iter() {
+            let key = JsValue::from(field.key.as_str());
+            let value = Reflect::get(target, &key)?;
+            let absent = value.is_undefined() || value.is_null();
+            if let Some(default) = field.default.as_ref() {
+                if absent {
+                    Reflect::set(target, &key, &copy_default(default)?)?;
+                }
+            } else if let Some(schema) = field.schema.as_ref() {
+                if absent && schema.has_defaults() {
+                    let object = JsValue::from(Object::new());
+                    schema.apply_defaults(&object)?;
+                    Reflect::set(target, &key, &object)?;
+                } else if is_plain_object(&value) {
+                    schema.apply_defaults(&value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single [`Schema`] violation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// Required property is absent
+    Missing {
+        path: String,
+    },
+    /// Property has an unexpected type
+    Type {
+        path: String,
+        expected: SchemaType,
+        found: String,
+    },
+}
+
+impl Violation {
+    /// Path of the offending property
+    pub fn path(&self) -> &str {
+        match self {
+            Violation::Missing { path } => path,
+            Violation::Type { path, .. } => path,
+        }
+    }
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::Missing { path } => write!(f, "`{path}` is required"),
+            Violation::Type { path, expected, found } => write!(f, "`{path}` must be {expected}, found {found}"),
+        }
+    }
+}
+
+fn violations_to_string(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .map(|violation| violation.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
 
 pub trait OptionsExt {
     /// "Construct a new `Options`.
@@ -49,69 +351,316 @@ pub trait OptionsExt {
         ret
     }
 
+    /// Schema describing these options. When supplied, defaults
+    /// declared by the schema are applied in [`initialize`](OptionsExt::initialize).
+    fn schema() -> Option<Schema>
+    where Self: Sized
+    {
+        None
+    }
+
     fn initialize(self)->Self
     where Self:wasm_bindgen::JsCast
     {
+        if let Some(schema) = Self::schema() {
+            let r = schema.apply_defaults(self.as_ref());
+            debug_assert!(
+                r.is_ok(),
+                "unable to apply option defaults: {:?}",
+                r.err()
+            );
+        }
         self
     }
 
-    fn set(self, mut key:&str, value:JsValue) -> Self
+    /// Validate these options against `schema`, returning
+    /// [`OptionsError::Validation`] with all violations found.
+    fn validate(&self, schema: &Schema) -> OptionsResult<()>
     where Self:wasm_bindgen::JsCast
     {
-        let mut target = self.as_ref().clone();
-
-        if key.contains("."){
-            let mut name_parts:Vec<&str> = key.split(".").collect();
-            key = name_parts.pop().unwrap();
-
-            for name in name_parts{
-                //log_info!("name: {}, target: {:?}", name, target);
-                let r = ::js_sys::Reflect::get(
-                    &target,
-                    &JsValue::from(name)
-                );
-
-                match r{
-                    Ok(r)=>{
-                        if !r.is_undefined(){
-                            target = r
-                        }else{
-                            let object = Object::new();
-                            let new_target = JsValue::from(object);
-                            //log_info!("new_target: {:?}", new_target);
-                            let _ = ::js_sys::Reflect::set(
-                                &target,
-                                &JsValue::from(name),
-                                &new_target,
-                            );
-
-                            target = new_target;
-                        }
-                    }
-                    Err(err)=>{
-                        debug_assert!(
-                            true,
-                            "unable to find {name}, err: {:?}",
-                            err
-                        );
-                    }
-                }
-            }
-
-            //log_info!("final: key: {}, target: {:?}", key, target);
+        let violations = schema.violations(self.as_ref())?;
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(OptionsError::Validation(violations))
         }
-    
-        let r = ::js_sys::Reflect::set(
-            &target,
-            &JsValue::from(key),
-            &value,
-        );
+    }
+
+    /// Set the property `key` to `value`. `key` can be a path such as
+    /// `a.b.c` or `items[2].name`; missing intermediate objects (or arrays,
+    /// if followed by an index) are created. Failures are ignored, use
+    /// [`try_set`](OptionsExt::try_set) to receive them.
+    fn set(self, key:&str, value:JsValue) -> Self
+    where Self:wasm_bindgen::JsCast
+    {
+        let r = set_path(self.as_ref(), key, &value);
         debug_assert!(
             r.is_ok(),
-            "setting properties should never fail on our dictionary objects"
+            "unable to set `{key}`: {:?}",
+            r.err()
         );
-        let _ = r;
-    
+
         self
     }
+
+    /// Set the property `key` to `value`, returning an [`OptionsError`]
+    /// identifying the failing path segment if the value can not be set
+    /// (for example when an intermediate segment is not an object).
+    fn try_set(self, key:&str, value:JsValue) -> OptionsResult<Self>
+    where Self:wasm_bindgen::JsCast
+    {
+        set_path(self.as_ref(), key, &value)?;
+        Ok(self)
+    }
+
+    /// Get the value of the property `key` converted to `T`.
+    /// `key` can be a path referring to a nested value.
+    /// A missing property produces `None` for `Option<T>` and
+    /// [`OptionsError::Missing`] for other types.
+    ///
+    /// `T` is bound on [`FromJsProp`] rather than `TryFrom<JsValue>`:
+    /// conversions check ranges and integrality and report failures
+    /// as [`OptionsError::Property`] naming the property, whereas
+    /// `TryFrom<JsValue>` is only implemented by few types and
+    /// returns the unconverted value as the error.
+    fn get<T>(&self, key: &str) -> OptionsResult<T>
+    where
+        Self: wasm_bindgen::JsCast,
+        T: FromJsProp,
+    {
+        match lookup(self.as_ref(), key)?.filter(|value| !value.is_undefined()) {
+            Some(value) => Ok(T::from_js_prop(key, value)?),
+            None => T::from_js_prop(key, JsValue::UNDEFINED)
+                .map_err(|_| OptionsError::Missing(key.to_string())),
+        }
+    }
+
+    /// Check if the property `key` (or a path) is present.
+    fn has(&self, key: &str) -> bool
+    where
+        Self: wasm_bindgen::JsCast,
+    {
+        let path = match Path::parse(key) {
+            Ok(path) => path,
+            Err(_) => return false,
+        };
+        match lookup_parent(self.as_ref(), &path) {
+            Ok(Some(parent)) => Reflect::has(&parent, &path.split_last().1.to_js_value()).unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// Remove the property `key` (or a path), returning
+    /// the removed value if the property was present.
+    fn remove(&self, key: &str) -> OptionsResult<Option<JsValue>>
+    where
+        Self: wasm_bindgen::JsCast,
+    {
+        let path = Path::parse(key)?;
+        let parent = match lookup_parent(self.as_ref(), &path)? {
+            Some(parent) => parent,
+            None => return Ok(None),
+        };
+
+        let name = path.split_last().1.to_js_value();
+        if !Reflect::has(&parent, &name)? {
+            return Ok(None);
+        }
+
+        let value = Reflect::get(&parent, &name)?;
+        Reflect::delete_property(parent.unchecked_ref::<Object>(), &name)?;
+        Ok(Some(value))
+    }
+
+    /// Deep-merge properties of `other` into this object. Nested
+    /// objects of `other` are merged recursively (and copied, never
+    /// shared with `other`), all other values from `other` replace
+    /// existing ones. Keys of `other` can use the same path syntax
+    /// as [`set`](OptionsExt::set).
+    fn merge(self, other: &JsValue) -> OptionsResult<Self>
+    where
+        Self: wasm_bindgen::JsCast,
+    {
+        merge_into(self.as_ref(), other)?;
+        Ok(self)
+    }
+
+    /// Create options from a Rust value implementing [`serde::Serialize`].
+    /// Struct field names honour `#[serde(rename)]` and nested structs
+    /// become nested objects.
+    #[cfg(feature = "serde")]
+    fn from_serde<T>(value: &T) -> OptionsResult<Self>
+    where
+        Self: wasm_bindgen::JsCast,
+        T: serde::Serialize + ?Sized,
+    {
+        let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+        let object = serde_path_to_error::serialize(value, &serializer).map_err(|err| OptionsError::Serde {
+            path: err.path().to_string(),
+            reason: err.inner().to_string(),
+        })?;
+
+        if !is_plain_object(&object) {
+            return Err(OptionsError::Conversion {
+                path: ".".to_string(),
+                reason: format!("expected an object, found {}", type_name(&object)),
+            });
+        }
+
+        Ok(object.unchecked_into())
+    }
+
+    /// Convert options into a Rust value implementing [`serde::Deserialize`].
+    #[cfg(feature = "serde")]
+    fn to_serde<T>(&self) -> OptionsResult<T>
+    where
+        Self: wasm_bindgen::JsCast,
+        T: serde::de::DeserializeOwned,
+    {
+        let deserializer = serde_wasm_bindgen::Deserializer::from(self.as_ref().clone());
+        serde_path_to_error::deserialize(deserializer).map_err(|err| OptionsError::Serde {
+            path: err.path().to_string(),
+            reason: err.inner().to_string(),
+        })
+    }
+}
+
+/// Deep copy of arrays and plain objects in `value`, so that options
+/// initialized from the same [`Schema`] do not share default values
+fn copy_default(value: &JsValue) -> OptionsResult<JsValue> {
+    if Array::is_array(value) {
+        let copy = Array::new();
+        for item in value.unchecked_ref::<Array>().iter() {
+            copy.push(&copy_default(&item)?);
+        }
+        Ok(copy.into())
+    } else if is_plain_object(value) {
+        let copy = Object::new();
+        for key in Object::keys(value.unchecked_ref::<Object>()).iter() {
+            Reflect::set(&copy, &key, &copy_default(&Reflect::get(value, &key)?)?)?;
+        }
+        Ok(copy.into())
+    } else {
+        Ok(value.clone())
+    }
+}
+
+fn is_object(value: &JsValue) -> bool {
+    value.is_object() || value.is_function()
+}
+
+fn is_plain_object(value: &JsValue) -> bool {
+    utils::js_type(value) == utils::JsType::PlainObject
+}
+
+/// Resolve the value holding the last segment of `path` without creating
+/// missing intermediate values. Returns `None` if an intermediate
+/// segment is not defined.
+fn lookup_parent(target: &JsValue, path: &Path) -> OptionsResult<Option<JsValue>> {
+    match utils::lookup_parent(target, path) {
+        Ok(parent) => Ok(Some(parent)),
+        Err(utils::Error::Undefined { .. }) => Ok(None),
+        Err(utils::Error::NotAnObject { path, found, key }) => Err(OptionsError::NotAnObject { path, found, key }),
+        Err(err) => Err(OptionsError::Property(err)),
+    }
+}
+
+/// Resolve the property value at `key` without creating intermediate values.
+fn lookup(target: &JsValue, key: &str) -> OptionsResult<Option<JsValue>> {
+    let path = Path::parse(key)?;
+    match lookup_parent(target, &path)? {
+        Some(parent) => Ok(Some(Reflect::get(&parent, &path.split_last().1.to_js_value())?)),
+        None => Ok(None),
+    }
+}
+
+/// Resolve the value holding the last segment of `path`, creating
+/// missing intermediate objects (or arrays) along the way.
+fn resolve_parent(target: &JsValue, path: &Path) -> OptionsResult<JsValue> {
+    let mut target = target.clone();
+    let segments = path.segments();
+    for (n, segment) in segments[..segments.len() - 1].iter().enumerate() {
+        let key = segment.to_js_value();
+        let value = Reflect::get(&target, &key)?;
+        if value.is_undefined() {
+            let value = match segments[n + 1] {
+                Segment::Index(_) => JsValue::from(Array::new()),
+                Segment::Key(_) => JsValue::from(Object::new()),
+            };
+            Reflect::set(&target, &key, &value)?;
+            target = value;
+        } else if is_object(&value) {
+            target = value;
+        } else {
+            return Err(OptionsError::CannotSet {
+                path: path.prefix(n + 1),
+                found: type_name(&value),
+                key: segments[n + 1].to_string(),
+            });
+        }
+    }
+
+    Ok(target)
+}
+
+fn set_path(target: &JsValue, key: &str, value: &JsValue) -> OptionsResult<()> {
+    let path = Path::parse(key)?;
+    let parent = resolve_parent(target, &path)?;
+    Reflect::set(&parent, &path.split_last().1.to_js_value(), value)?;
+    Ok(())
+}
+
+fn merge_into(target: &JsValue, source: &JsValue) -> OptionsResult<()> {
+    if !is_object(source) {
+        return Err(OptionsError::Conversion {
+            path: "<merge source>".to_string(),
+            reason: format!("expected an object, found {}", type_name(source)),
+        });
+    }
+
+    for key in Object::keys(source.unchecked_ref::<Object>()).iter() {
+        let value = Reflect::get(source, &key)?;
+        let path = Path::parse(&key.as_string().unwrap_or_default())?;
+        let parent = resolve_parent(target, &path)?;
+        let name = path.split_last().1.to_js_value();
+        if is_plain_object(&value) {
+            // merge into a fresh object rather than storing `value`, so
+            // that later changes to the options do not modify `source`
+            let mut existing = Reflect::get(&parent, &name)?;
+            if !is_plain_object(&existing) {
+                existing = Object::new().into();
+                Reflect::set(&parent, &name, &existing)?;
+            }
+            merge_into(&existing, &value)?;
+        } else {
+            Reflect::set(&parent, &name, &value)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    impl OptionsExt for Object {}
+
+    #[wasm_bindgen_test]
+    fn merge_copies_nested_objects() {
+        let other = Object::new();
+        let nested = Object::new();
+        Reflect::set(&nested, &"x".into(), &1.into()).unwrap();
+        Reflect::set(&other, &"k".into(), &nested).unwrap();
+
+        let options = Object::new().merge(&other).unwrap();
+        let options = options.try_set("k.x", 2.into()).unwrap();
+        let options = options.try_set("k.y", 3.into()).unwrap();
+
+        assert_eq!(options.get::<u32>("k.x").unwrap(), 2);
+        assert_eq!(options.get::<u32>("k.y").unwrap(), 3);
+        assert_eq!(Reflect::get(&nested, &"x".into()).unwrap(), 1);
+        assert!(!Reflect::has(&nested, &"y".into()).unwrap());
+    }
 }
@@ -3,10 +3,37 @@
 //! as well as a browser-based panic hook.  (the browser-based panic hook activates a full-screen debug 
 //! information output in case of a panic - useful on mobile devices or where 
 //! the user otherwise has no access to console/developer tools)
-//! 
+//!
+//! [`PanicHookBuilder`] allows a panic to be delivered to multiple sinks
+//! (console, popup, Rust handlers and JavaScript callbacks) with a custom
+//! message formatter:
+//!
+//! ```ignore
+//! PanicHookBuilder::new()
+//!     .console()
+//!     .popup()
+//!     .handler(|report, _message| upload(report))
+//!     .formatter(|report| format!("{} at {:?}", report.message, report.location))
+//!     .install();
+//! ```
+//!
+//! The host page can subscribe to panics using the exported `subscribe_panics()`
+//! function (requires the panic hook to be installed via [`PanicHookBuilder`]).
+//!
+//! Panic reports can be persisted across page reloads using
+//! [`PanicHookBuilder::persist()`] and retrieved on the next start using
+//! [`last_reports()`] (or the exported `get_panic_reports()` function), or
+//! [`last_reports_from()`] to read a specific storage before the hook is installed.
+//!
 
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
+use js_sys::{Array, Function, Object, Reflect, JSON};
 use workflow_panic_hook::{set_once, Type, show_logs as show_wasm_logs};
+use crate::{env, init, log};
+use crate::utils::{call_method, resolve_global_path};
 
 /// Initialize panic hook in console mode
 #[wasm_bindgen]
@@ -14,14 +41,511 @@ pub fn init_console_panic_hook(){
     set_once(Type::Console);
 }
 
-/// Initialize panic hook in browser mode
+/// Initialize panic hook in browser mode. Falls back to console mode
+/// in environments without DOM (web workers, Node.js etc.)
 #[wasm_bindgen]
 pub fn init_popup_panic_hook(){
-    set_once(Type::Popup);
+    if env::has_dom() {
+        set_once(Type::Popup);
+    } else {
+        set_once(Type::Console);
+    }
 }
 
-/// Present panic logs to the user
+/// Present panic logs to the user (has no effect in environments without DOM)
 #[wasm_bindgen]
 pub fn show_panic_hook_logs(){
-    show_wasm_logs();
+    if env::has_dom() {
+        show_wasm_logs();
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(msg: &str);
+}
+
+/// `id` of the DOM element created by the popup sink
+const POPUP_ELEMENT_ID: &str = "workflow-panic-popup";
+/// `class` of the DOM element created by the popup sink (distinct from the
+/// `wasm-logs` class of the `workflow_panic_hook` log elements)
+const POPUP_CLASS_NAME: &str = "workflow-panic-popup";
+const POPUP_STYLE: &str = "position:fixed;top:0;left:0;right:0;bottom:0;z-index:2147483647;\
+    margin:0;padding:16px;overflow:auto;white-space:pre-wrap;word-break:break-all;\
+    background:#fff;color:#a00;font-size:12px;";
+
+/// Information about a panic delivered to the panic hook sinks
+#[derive(Debug, Clone)]
+pub struct PanicReport {
+    /// Panic message
+    pub message: String,
+    /// Rust source location (`file:line:column`)
+    pub location: Option<String>,
+    /// JavaScript stack trace captured at the time of the panic
+    pub js_stack: Option<String>,
+    /// Time of the panic (milliseconds since UNIX epoch)
+    pub timestamp: f64,
+    /// Contents of the panic logs (see [`show_panic_hook_logs()`]) and messages
+    /// retained by the [`log`] ring buffer preceding the panic
+    pub logs: Option<String>,
+}
+
+impl PanicReport {
+    /// Create a report from a JavaScript object created by [`to_js_value()`](PanicReport::to_js_value)
+    pub fn from_js_value(value: &JsValue) -> Option<PanicReport> {
+        let get = |name: &str| Reflect::get(value, &name.into()).ok();
+        let string = |name: &str| get(name).and_then(|value| value.as_string());
+        Some(PanicReport {
+            message: string("message")?,
+            location: string("location"),
+            js_stack: string("stack"),
+            timestamp: get("timestamp")?.as_f64()?,
+            logs: string("logs"),
+        })
+    }
+
+    /// Create a JavaScript object `{ message, location, stack, timestamp, logs }`
+    pub fn to_js_value(&self) -> JsValue {
+        let object = Object::new();
+        let optional = |value: &Option<String>| {
+            value.as_deref().map(JsValue::from).unwrap_or(JsValue::UNDEFINED)
+        };
+        let _ = Reflect::set(&object, &"message".into(), &self.message.as_str().into());
+        let _ = Reflect::set(&object, &"location".into(), &optional(&self.location));
+        let _ = Reflect::set(&object, &"stack".into(), &optional(&self.js_stack));
+        let _ = Reflect::set(&object, &"timestamp".into(), &self.timestamp.into());
+        let _ = Reflect::set(&object, &"logs".into(), &optional(&self.logs));
+        object.into()
+    }
+}
+
+impl std::fmt::Display for PanicReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.location.as_ref() {
+            Some(location) => write!(f, "panicked at {location}:\n{}", self.message)?,
+            None => write!(f, "panicked:\n{}", self.message)?,
+        }
+        if let Some(stack) = self.js_stack.as_ref() {
+            write!(f, "\n\nStack:\n\n{stack}")?;
+        }
+        // trailing whitespace prevents Safari devtools from reformatting the message
+        write!(f, "\n\n")
+    }
+}
+
+/// Panic message formatter
+pub type PanicFormatter = dyn Fn(&PanicReport) -> String;
+/// Rust panic handler receiving the report and the formatted message
+pub type PanicHandler = dyn Fn(&PanicReport, &str);
+
+#[derive(Clone)]
+enum Sink {
+    Console,
+    Popup,
+    Handler(Rc<PanicHandler>),
+    Function(Function),
+}
+
+/// Storage used to persist panic reports (see [`PanicHookBuilder::persist()`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanicStorage {
+    /// `localStorage` (retained across browser sessions)
+    Local,
+    /// `sessionStorage` (retained across reloads of the page)
+    Session,
+    /// Memory of the current WASM instance
+    Memory,
+}
+
+impl PanicStorage {
+    fn global_name(&self) -> Option<&'static str> {
+        match self {
+            PanicStorage::Local => Some("localStorage"),
+            PanicStorage::Session => Some("sessionStorage"),
+            PanicStorage::Memory => None,
+        }
+    }
+}
+
+/// Panic hook configuration installed by [`PanicHookBuilder::install()`]
+#[derive(Clone)]
+struct PanicHook {
+    sinks: Vec<Sink>,
+    formatter: Option<Rc<PanicFormatter>>,
+    capture_js_stack: bool,
+    persist: Option<(PanicStorage, usize)>,
+}
+
+thread_local! {
+    static HOOK: RefCell<Option<PanicHook>> = const { RefCell::new(None) };
+    static SUBSCRIBERS: RefCell<Vec<Function>> = const { RefCell::new(Vec::new()) };
+    static REPORTS: RefCell<VecDeque<PanicReport>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// Builder for a panic hook delivering panics to multiple sinks
+#[derive(Clone)]
+pub struct PanicHookBuilder {
+    sinks: Vec<Sink>,
+    formatter: Option<Rc<PanicFormatter>>,
+    capture_js_stack: bool,
+    persist: Option<(PanicStorage, usize)>,
+}
+
+impl Default for PanicHookBuilder {
+    fn default() -> Self {
+        PanicHookBuilder::new()
+    }
+}
+
+impl PanicHookBuilder {
+    /// Create a builder without sinks, capturing the JavaScript stack
+    pub fn new() -> PanicHookBuilder {
+        PanicHookBuilder {
+            sinks: Vec::new(),
+            formatter: None,
+            capture_js_stack: true,
+            persist: None,
+        }
+    }
+
+    /// Output panics using `console.error()`
+    pub fn console(mut self) -> Self {
+        self.sinks.push(Sink::Console);
+        self
+    }
+
+    /// Output panics to a full-screen DOM element. Falls back to
+    /// the console in environments without DOM.
+    pub fn popup(mut self) -> Self {
+        self.sinks.push(Sink::Popup);
+        self
+    }
+
+    /// Deliver panics to a Rust closure
+    pub fn handler<F>(mut self, handler: F) -> Self
+    where F: Fn(&PanicReport, &str) + 'static
+    {
+        self.sinks.push(Sink::Handler(Rc::new(handler)));
+        self
+    }
+
+    /// Deliver panics to a JavaScript function (such as a [`Callback`](crate::callback::Callback))
+    /// invoked with the formatted message and the report object
+    /// (see [`PanicReport::to_js_value()`]).
+    pub fn callback<C>(mut self, callback: &C) -> Self
+    where C: AsRef<Function>
+    {
+        self.sinks.push(Sink::Function(callback.as_ref().clone()));
+        self
+    }
+
+    /// Use a custom message formatter instead of [`PanicReport`]'s `Display`
+    pub fn formatter<F>(mut self, formatter: F) -> Self
+    where F: Fn(&PanicReport) -> String + 'static
+    {
+        self.formatter = Some(Rc::new(formatter));
+        self
+    }
+
+    /// Capture the JavaScript stack trace (enabled by default)
+    pub fn capture_js_stack(mut self, capture: bool) -> Self {
+        self.capture_js_stack = capture;
+        self
+    }
+
+    /// Keep the last `limit` panic reports in `storage`. Browser storage falls
+    /// back to memory in environments where it is not available (Node.js etc.)
+    pub fn persist(mut self, storage: PanicStorage, limit: usize) -> Self {
+        self.persist = Some((storage, limit));
+        self
+    }
+
+    /// Install the panic hook, replacing any previously installed hook
+    pub fn install(self) {
+        let hook = PanicHook {
+            sinks: self.sinks,
+            formatter: self.formatter,
+            capture_js_stack: self.capture_js_stack,
+            persist: self.persist,
+        };
+        HOOK.with(|current| *current.borrow_mut() = Some(hook));
+
+        std::panic::set_hook(Box::new(|info| {
+            let message = if let Some(message) = info.payload().downcast_ref::<&str>() {
+                message.to_string()
+            } else if let Some(message) = info.payload().downcast_ref::<String>() {
+                message.clone()
+            } else {
+                "Box<dyn Any>".to_string()
+            };
+            let location = info
+                .location()
+                .map(|location| format!("{}:{}:{}", location.file(), location.line(), location.column()));
+            dispatch(message, location);
+        }));
+    }
+}
+
+fn js_stack() -> Option<String> {
+    Reflect::get(&js_sys::Error::new(""), &"stack".into())
+        .ok()
+        .and_then(|stack| stack.as_string())
+}
+
+fn dispatch(message: String, location: Option<String>) {
+    let hook = HOOK.with(|hook| hook.try_borrow().ok().and_then(|hook| hook.clone()));
+    let hook = match hook {
+        Some(hook) => hook,
+        None => return,
+    };
+
+    let report = PanicReport {
+        message,
+        location,
+        js_stack: if hook.capture_js_stack { js_stack() } else { None },
+        timestamp: js_sys::Date::now(),
+        logs: report_logs(),
+    };
+    if let Some((storage, limit)) = hook.persist {
+        store_report(storage, limit, &report);
+    }
+
+    let text = match hook.formatter {
+        Some(formatter) => formatter(&report),
+        None => report.to_string(),
+    };
+
+    for sink in hook.sinks.iter() {
+        match sink {
+            Sink::Console => console_error(&text),
+            Sink::Popup => {
+                if show_popup(&popup_text(&text)).is_err() {
+                    console_error(&text);
+                }
+            }
+            Sink::Handler(handler) => handler(&report, &text),
+            Sink::Function(function) => {
+                let _ = function.call2(&JsValue::UNDEFINED, &text.as_str().into(), &report.to_js_value());
+            }
+        }
+    }
+
+    let subscribers = SUBSCRIBERS.with(|subscribers| subscribers.borrow().clone());
+    for subscriber in subscribers.iter() {
+        let _ = subscriber.call2(&JsValue::UNDEFINED, &text.as_str().into(), &report.to_js_value());
+    }
+}
+
+/// Append `text` to the popup element, creating it if needed
+fn show_popup(text: &str) -> Result<(), JsValue> {
+    if !env::has_dom() {
+        return Err(JsValue::from("DOM is not available"));
+    }
+
+    let document = resolve_global_path("document")?;
+    let mut element = call_method(&document, "getElementById", (POPUP_ELEMENT_ID,))?;
+    if element.is_null() {
+        element = call_method(&document, "createElement", ("pre",))?;
+        Reflect::set(&element, &"id".into(), &POPUP_ELEMENT_ID.into())?;
+        Reflect::set(&element, &"className".into(), &POPUP_CLASS_NAME.into())?;
+        call_method(&element, "setAttribute", ("style", POPUP_STYLE))?;
+        let body = resolve_global_path("document.body")?;
+        call_method(&body, "appendChild", (element.clone(),))?;
+    }
+
+    let content = Reflect::get(&element, &"textContent".into())?
+        .as_string()
+        .unwrap_or_default();
+    Reflect::set(&element, &"textContent".into(), &(content + text).into())?;
+    Ok(())
+}
+
+/// Contents of the `wasm-logs` elements created by `workflow_panic_hook`, if any
+fn popup_logs() -> Option<String> {
+    if !env::has_dom() {
+        return None;
+    }
+
+    let document = resolve_global_path("document").ok()?;
+    let elements = call_method(&document, "querySelectorAll", (".wasm-logs",)).ok()?;
+    let logs = Array::from(&elements)
+        .iter()
+        .filter_map(|element| Reflect::get(&element, &"textContent".into()).ok()?.as_string())
+        .collect::<Vec<_>>()
+        .join("");
+    (!logs.is_empty()).then_some(logs)
+}
+
+/// Lines of messages retained by the [`log`] ring buffer
+fn buffered_logs() -> String {
+    log::buffered()
+        .iter()
+        .map(|record| record.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Logs captured in [`PanicReport::logs`]
+fn report_logs() -> Option<String> {
+    let logs = [popup_logs().unwrap_or_default(), buffered_logs()]
+        .into_iter()
+        .filter(|logs| !logs.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    (!logs.is_empty()).then_some(logs)
+}
+
+/// Panic message followed by the messages retained by the [`log`] ring buffer
+fn popup_text(text: &str) -> String {
+    let logs = buffered_logs();
+    if logs.is_empty() {
+        text.to_string()
+    } else {
+        format!("{text}Logs:\n\n{logs}\n\n")
+    }
+}
+
+/// Key of the browser storage item holding panic reports
+fn storage_key() -> String {
+    format!("workflow-panic-reports:{}", init::namespace())
+}
+
+/// Obtain `localStorage` or `sessionStorage` (access may throw in sandboxed frames)
+fn browser_storage(storage: PanicStorage) -> Option<JsValue> {
+    let storage = resolve_global_path(storage.global_name()?).ok()?;
+    storage.is_object().then_some(storage)
+}
+
+fn read_reports(storage: &JsValue) -> Option<Vec<PanicReport>> {
+    let json = call_method(storage, "getItem", (storage_key(),)).ok()?.as_string()?;
+    let reports = JSON::parse(&json).ok()?;
+    Some(Array::from(&reports).iter().filter_map(|report| PanicReport::from_js_value(&report)).collect())
+}
+
+fn write_reports<'r>(storage: &JsValue, reports: impl Iterator<Item = &'r PanicReport>) -> Option<()> {
+    let array = reports.map(|report| report.to_js_value()).collect::<Array>();
+    let json = JSON::stringify(&array).ok()?;
+    call_method(storage, "setItem", (storage_key(), json)).ok()?;
+    Some(())
+}
+
+fn store_report(storage: PanicStorage, limit: usize, report: &PanicReport) {
+    let reports = REPORTS.with(|reports| {
+        let mut reports = reports.borrow_mut();
+        reports.push_back(report.clone());
+        while reports.len() > limit {
+            reports.pop_front();
+        }
+        reports.clone()
+    });
+
+    if let Some(storage) = browser_storage(storage) {
+        let mut stored = read_reports(&storage).unwrap_or_default();
+        stored.push(report.clone());
+        let skip = stored.len().saturating_sub(limit);
+        if write_reports(&storage, stored.iter().skip(skip)).is_none() {
+            // quota exceeded or storage is not writable
+            write_reports(&storage, reports.iter());
+        }
+    }
+}
+
+/// Storage configured using [`PanicHookBuilder::persist()`], if any
+fn persisted_storage() -> Option<PanicStorage> {
+    HOOK.with(|hook| hook.borrow().as_ref().and_then(|hook| hook.persist))
+        .map(|(storage, _)| storage)
+}
+
+fn memory_reports() -> Vec<PanicReport> {
+    REPORTS.with(|reports| reports.borrow().iter().cloned().collect())
+}
+
+/// Panic reports persisted in `storage`, oldest first. Reads memory
+/// if browser storage is not available (as [`PanicHookBuilder::persist()`]).
+/// Does not require the panic hook to be installed, allowing reports of
+/// previous page loads to be read before the hook is configured.
+pub fn last_reports_from(storage: PanicStorage) -> Vec<PanicReport> {
+    match browser_storage(storage) {
+        Some(storage) => read_reports(&storage).unwrap_or_default(),
+        None => memory_reports(),
+    }
+}
+
+/// Remove panic reports persisted in `storage` (and in memory)
+pub fn clear_reports_from(storage: PanicStorage) {
+    REPORTS.with(|reports| reports.borrow_mut().clear());
+    if let Some(storage) = browser_storage(storage) {
+        let _ = call_method(&storage, "removeItem", (storage_key(),));
+    }
+}
+
+/// Panic reports persisted by the panic hook (see [`PanicHookBuilder::persist()`]),
+/// oldest first. If the hook does not persist reports (or is not installed),
+/// reports found in both `localStorage` and `sessionStorage` are returned.
+pub fn last_reports() -> Vec<PanicReport> {
+    if let Some(storage) = persisted_storage() {
+        return last_reports_from(storage);
+    }
+
+    let storages = [PanicStorage::Local, PanicStorage::Session]
+        .into_iter()
+        .filter_map(browser_storage)
+        .collect::<Vec<_>>();
+    if storages.is_empty() {
+        return memory_reports();
+    }
+
+    let mut reports = storages
+        .iter()
+        .flat_map(|storage| read_reports(storage).unwrap_or_default())
+        .collect::<Vec<_>>();
+    reports.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+    reports
+}
+
+/// Remove persisted panic reports from the storage used by the panic hook
+/// or, if the hook does not persist reports, from both `localStorage`
+/// and `sessionStorage`
+pub fn clear_reports() {
+    match persisted_storage() {
+        Some(storage) => clear_reports_from(storage),
+        None => {
+            clear_reports_from(PanicStorage::Local);
+            clear_reports_from(PanicStorage::Session);
+        }
+    }
+}
+
+/// Panic reports persisted by the panic hook as an array of
+/// `{ message, location, stack, timestamp, logs }` objects (oldest first).
+/// See [`last_reports()`].
+#[wasm_bindgen]
+pub fn get_panic_reports() -> Array {
+    last_reports().iter().map(|report| report.to_js_value()).collect()
+}
+
+/// Remove persisted panic reports (see [`clear_reports()`])
+#[wasm_bindgen]
+pub fn clear_panic_reports() {
+    clear_reports();
+}
+
+/// Subscribe `callback` to panics. The callback is invoked with the formatted
+/// message and the report object `{ message, location, stack, timestamp, logs }`.
+/// Requires the panic hook to be installed via [`PanicHookBuilder`].
+#[wasm_bindgen]
+pub fn subscribe_panics(callback: Function) {
+    SUBSCRIBERS.with(|subscribers| subscribers.borrow_mut().push(callback));
+}
+
+/// Unsubscribe `callback` previously passed to [`subscribe_panics()`]
+#[wasm_bindgen]
+pub fn unsubscribe_panics(callback: &Function) -> bool {
+    SUBSCRIBERS.with(|subscribers| {
+        let mut subscribers = subscribers.borrow_mut();
+        let len = subscribers.len();
+        subscribers.retain(|subscriber| !Object::is(subscriber, callback));
+        subscribers.len() != len
+    })
 }
@@ -0,0 +1,207 @@
+//!
+//! Property path parsing. A path addresses a nested value within
+//! a JavaScript object using dot-separated property names and
+//! array indices, for example `response.data.items[0].id`.
+//!
+
+use wasm_bindgen::prelude::*;
+use thiserror::Error;
+
+/// Errors produced by the [`path`](self) module
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PathError {
+    /// Path string is empty
+    #[error("Path is empty")]
+    Empty,
+
+    /// Path string is malformed
+    #[error("Invalid path `{path}`: {reason}")]
+    Invalid {
+        path: String,
+        reason: String,
+    },
+}
+
+/// A single segment of a [`Path`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// Object property name
+    Key(String),
+    /// Array index (`[N]`)
+    Index(u32),
+}
+
+impl Segment {
+    /// Property key suitable for use with [`js_sys::Reflect`]
+    pub fn to_js_value(&self) -> JsValue {
+        match self {
+            Segment::Key(key) => JsValue::from(key.as_str()),
+            Segment::Index(index) => JsValue::from(*index),
+        }
+    }
+}
+
+impl std::fmt::Display for Segment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Segment::Key(key) => write!(f, "{key}"),
+            Segment::Index(index) => write!(f, "[{index}]"),
+        }
+    }
+}
+
+/// Parsed property path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    segments: Vec<Segment>,
+}
+
+impl Path {
+    /// Parse a path such as `a.b[2].c`
+    pub fn parse(path: &str) -> Result<Path, PathError> {
+        if path.is_empty() {
+            return Err(PathError::Empty);
+        }
+
+        let invalid = |reason: &str| PathError::Invalid {
+            path: path.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let mut segments = Vec::new();
+        for (n, part) in path.split('.').enumerate() {
+            let (name, mut rest) = match part.find('[') {
+                Some(pos) => part.split_at(pos),
+                None => (part, ""),
+            };
+
+            if name.is_empty() && (n > 0 || rest.is_empty()) {
+                return Err(invalid("empty property name"));
+            }
+            if name.contains(']') {
+                return Err(invalid("unexpected `]`"));
+            }
+            if !name.is_empty() {
+                segments.push(Segment::Key(name.to_string()));
+            }
+
+            while !rest.is_empty() {
+                if !rest.starts_with('[') {
+                    return Err(invalid("unexpected characters after `]`"));
+                }
+                let end = rest
+                    .find(']')
+                    .ok_or_else(|| invalid("missing closing `]`"))?;
+                let index = rest[1..end]
+                    .parse::<u32>()
+                    .map_err(|_| invalid(&format!("`{}` is not an array index", &rest[1..end])))?;
+                segments.push(Segment::Index(index));
+                rest = &rest[end + 1..];
+            }
+        }
+
+        Ok(Path { segments })
+    }
+
+    /// Path segments
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Render the first `len` segments of the path
+    pub fn prefix(&self, len: usize) -> String {
+        render(&self.segments[..len.min(self.segments.len())])
+    }
+
+    /// Split the path into the leading segments and the last segment
+    pub fn split_last(&self) -> (&[Segment], &Segment) {
+        let (last, parents) = self
+            .segments
+            .split_last()
+            .expect("path always contains at least one segment");
+        (parents, last)
+    }
+}
+
+impl std::str::FromStr for Path {
+    type Err = PathError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Path::parse(s)
+    }
+}
+
+impl std::fmt::Display for Path {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", render(&self.segments))
+    }
+}
+
+fn render(segments: &[Segment]) -> String {
+    let mut s = String::new();
+    for segment in segments {
+        if matches!(segment, Segment::Key(_)) && !s.is_empty() {
+            s.push('.');
+        }
+        s.push_str(&segment.to_string());
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid(path: &str) -> bool {
+        matches!(Path::parse(path), Err(PathError::Invalid { .. }))
+    }
+
+    #[test]
+    fn parse_valid_paths() {
+        assert_eq!(Path::parse("a").unwrap().segments(), &[Segment::Key("a".into())]);
+        assert_eq!(
+            Path::parse("a.b[2].c").unwrap().segments(),
+            &[
+                Segment::Key("a".into()),
+                Segment::Key("b".into()),
+                Segment::Index(2),
+                Segment::Key("c".into()),
+            ]
+        );
+        assert_eq!(
+            Path::parse("a[0][1]").unwrap().segments(),
+            &[Segment::Key("a".into()), Segment::Index(0), Segment::Index(1)]
+        );
+        assert_eq!(
+            Path::parse("[0].a").unwrap().segments(),
+            &[Segment::Index(0), Segment::Key("a".into())]
+        );
+    }
+
+    #[test]
+    fn parse_invalid_paths() {
+        assert_eq!(Path::parse(""), Err(PathError::Empty));
+        for path in [".", "a.", ".a", "a..b", "a.[0]", "a[0]b", "a[", "a[0", "a[-1]", "a[]", "a[x]", "a]", "a[0]]"] {
+            assert!(invalid(path), "`{path}` should be rejected");
+        }
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for path in ["a", "a.b", "a.b[2].c", "a[0][1]", "[0].a"] {
+            assert_eq!(Path::parse(path).unwrap().to_string(), path);
+        }
+    }
+
+    #[test]
+    fn prefix_and_split_last() {
+        let path: Path = "a.b[2].c".parse().unwrap();
+        assert_eq!(path.prefix(0), "");
+        assert_eq!(path.prefix(2), "a.b");
+        assert_eq!(path.prefix(3), "a.b[2]");
+        assert_eq!(path.prefix(10), "a.b[2].c");
+
+        let (parents, last) = path.split_last();
+        assert_eq!(parents.len(), 3);
+        assert_eq!(last, &Segment::Key("c".into()));
+    }
+}
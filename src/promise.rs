@@ -0,0 +1,274 @@
+//!
+//! JavaScript `Promise` interop: creation of promises from Rust futures
+//! ([`PromiseExt::from_future()`]), promises resolved from Rust ([`resolvable()`]),
+//! `Promise.all()` / `race()` / `any()` / `allSettled()` over Rust futures
+//! and timeouts backed by the [`timers`] module.
+//!
+//! ```ignore
+//! let (promise, resolver) = promise::resolvable();
+//! // hand over `promise` to JavaScript, resolve it later
+//! resolver.resolve(&JsValue::from("done"));
+//!
+//! let values = promise::promise_all(vec![fetch(a), fetch(b)]).await?;
+//! let value = promise::timeout(5000, fetch(c)).await??;
+//! ```
+//!
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use js_sys::{Array, Function, Promise, Reflect};
+use thiserror::Error;
+use crate::callback::Callback;
+use crate::timers;
+use crate::utils::{call_method, resolve_global_path};
+
+/// Errors produced by the [`promise`](self) module
+#[derive(Error, Debug)]
+pub enum PromiseError {
+    /// Promise was rejected with the contained value
+    #[error("{}", crate::utils::describe(.0, 2))]
+    JsValue(JsValue),
+
+    /// Operation did not complete within the given number of milliseconds
+    #[error("Timed out after {0}ms")]
+    Timeout(u32),
+
+    #[error(transparent)]
+    Timers(#[from] timers::Error),
+}
+
+impl From<JsValue> for PromiseError {
+    fn from(value: JsValue) -> Self {
+        PromiseError::JsValue(value)
+    }
+}
+
+impl From<PromiseError> for JsValue {
+    fn from(err: PromiseError) -> Self {
+        crate::error::Error::from(err).into()
+    }
+}
+
+pub type PromiseResult<T> = std::result::Result<T, PromiseError>;
+
+/// Extension methods for [`js_sys::Promise`]
+pub trait PromiseExt {
+    /// Create a promise resolved with the `Ok` value or rejected
+    /// with the `Err` value of `future`
+    fn from_future<F, T, E>(future: F) -> Promise
+    where
+        F: Future<Output = Result<T, E>> + 'static,
+        T: Into<JsValue>,
+        E: Into<JsValue>;
+
+    /// Convert the promise into a Rust future
+    fn into_future(self) -> JsFuture;
+}
+
+impl PromiseExt for Promise {
+    fn from_future<F, T, E>(future: F) -> Promise
+    where
+        F: Future<Output = Result<T, E>> + 'static,
+        T: Into<JsValue>,
+        E: Into<JsValue>,
+    {
+        future_to_promise(async move {
+            future.await.map(Into::into).map_err(Into::into)
+        })
+    }
+
+    fn into_future(self) -> JsFuture {
+        JsFuture::from(self)
+    }
+}
+
+/// One-shot callback settling a promise (see [`Resolver::callbacks()`])
+pub type ResolverCallback = Callback<dyn FnMut(JsValue)>;
+
+/// Functions resolving or rejecting a promise created by [`resolvable()`]
+#[derive(Debug, Clone)]
+pub struct Resolver {
+    resolve: Function,
+    reject: Function,
+}
+
+impl Resolver {
+    /// Resolve the promise with `value`
+    pub fn resolve(self, value: &JsValue) {
+        let _ = self.resolve.call1(&JsValue::UNDEFINED, value);
+    }
+
+    /// Reject the promise with `reason`
+    pub fn reject(self, reason: &JsValue) {
+        let _ = self.reject.call1(&JsValue::UNDEFINED, reason);
+    }
+
+    /// One-shot callbacks resolving and rejecting the promise, suitable for
+    /// passing to JavaScript (e.g. as event listeners). Only the first
+    /// invocation of either callback settles the promise.
+    pub fn callbacks(&self) -> (ResolverCallback, ResolverCallback) {
+        let mut resolver = Some(self.clone());
+        let resolve = Callback::new(move |value: JsValue| {
+            if let Some(resolver) = resolver.take() {
+                resolver.resolve(&value);
+            }
+        });
+        let mut resolver = Some(self.clone());
+        let reject = Callback::new(move |reason: JsValue| {
+            if let Some(resolver) = resolver.take() {
+                resolver.reject(&reason);
+            }
+        });
+        (resolve, reject)
+    }
+}
+
+/// Create a pending promise together with the [`Resolver`] that settles it
+pub fn resolvable() -> (Promise, Resolver) {
+    let mut functions = None;
+    let promise = Promise::new(&mut |resolve, reject| {
+        functions = Some((resolve, reject));
+    });
+    let (resolve, reject) = functions.expect("Promise executor is invoked synchronously");
+    (promise, Resolver { resolve, reject })
+}
+
+/// Outcome of a future passed to [`promise_all_settled()`]
+#[derive(Debug, Clone)]
+pub enum Settled {
+    Fulfilled(JsValue),
+    Rejected(JsValue),
+}
+
+fn to_promises<I, F, T, E>(futures: I) -> Array
+where
+    I: IntoIterator<Item = F>,
+    F: Future<Output = Result<T, E>> + 'static,
+    T: Into<JsValue>,
+    E: Into<JsValue>,
+{
+    futures.into_iter().map(|future| JsValue::from(Promise::from_future(future))).collect()
+}
+
+/// Invoke the static method `name` of the global `Promise` class
+/// (`Promise.any()` and `Promise.allSettled()` are not bound by `js_sys`)
+fn promise_static(name: &str, promises: Array) -> PromiseResult<Promise> {
+    let class = resolve_global_path("Promise").map_err(|err| PromiseError::JsValue(err.into()))?;
+    let promise = call_method(&class, name, (promises,)).map_err(|err| PromiseError::JsValue(err.into()))?;
+    Ok(promise.unchecked_into())
+}
+
+/// Wait for all `futures` to complete (`Promise.all()`). Fails with the
+/// first error produced by any of the futures.
+pub async fn promise_all<I, F, T, E>(futures: I) -> PromiseResult<Vec<JsValue>>
+where
+    I: IntoIterator<Item = F>,
+    F: Future<Output = Result<T, E>> + 'static,
+    T: Into<JsValue>,
+    E: Into<JsValue>,
+{
+    let values = JsFuture::from(Promise::all(&to_promises(futures))).await?;
+    Ok(Array::from(&values).to_vec())
+}
+
+/// Wait for the first of `futures` to complete (`Promise.race()`)
+pub async fn promise_race<I, F, T, E>(futures: I) -> PromiseResult<JsValue>
+where
+    I: IntoIterator<Item = F>,
+    F: Future<Output = Result<T, E>> + 'static,
+    T: Into<JsValue>,
+    E: Into<JsValue>,
+{
+    Ok(JsFuture::from(Promise::race(&to_promises(futures))).await?)
+}
+
+/// Wait for the first of `futures` to succeed (`Promise.any()`). Fails with
+/// an `AggregateError` if all futures fail.
+pub async fn promise_any<I, F, T, E>(futures: I) -> PromiseResult<JsValue>
+where
+    I: IntoIterator<Item = F>,
+    F: Future<Output = Result<T, E>> + 'static,
+    T: Into<JsValue>,
+    E: Into<JsValue>,
+{
+    let promise = promise_static("any", to_promises(futures))?;
+    Ok(JsFuture::from(promise).await?)
+}
+
+/// Wait for all `futures` to complete, successfully or not (`Promise.allSettled()`)
+pub async fn promise_all_settled<I, F, T, E>(futures: I) -> PromiseResult<Vec<Settled>>
+where
+    I: IntoIterator<Item = F>,
+    F: Future<Output = Result<T, E>> + 'static,
+    T: Into<JsValue>,
+    E: Into<JsValue>,
+{
+    let promise = promise_static("allSettled", to_promises(futures))?;
+    let results = JsFuture::from(promise).await?;
+    Array::from(&results)
+        .iter()
+        .map(|result| {
+            let status = Reflect::get(&result, &"status".into())?;
+            if status.as_string().as_deref() == Some("fulfilled") {
+                Ok(Settled::Fulfilled(Reflect::get(&result, &"value".into())?))
+            } else {
+                Ok(Settled::Rejected(Reflect::get(&result, &"reason".into())?))
+            }
+        })
+        .collect()
+}
+
+/// Wait for `ms` milliseconds. Dropping the future cancels the underlying timeout.
+pub async fn sleep(ms: u32) -> PromiseResult<()> {
+    let (promise, resolver) = resolvable();
+    let mut resolver = Some(resolver);
+    let closure = Closure::wrap(Box::new(move || {
+        if let Some(resolver) = resolver.take() {
+            resolver.resolve(&JsValue::UNDEFINED);
+        }
+    }) as Box<dyn FnMut()>);
+    let _handle = timers::set_timeout(&closure, ms)?;
+    JsFuture::from(promise).await?;
+    Ok(())
+}
+
+/// Future returned by [`timeout()`]
+struct Timeout<F, S> {
+    future: Pin<Box<F>>,
+    sleep: Pin<Box<S>>,
+    ms: u32,
+}
+
+impl<F, S> Future for Timeout<F, S>
+where
+    F: Future,
+    S: Future<Output = PromiseResult<()>>,
+{
+    type Output = PromiseResult<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        match self.sleep.as_mut().poll(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(Err(PromiseError::Timeout(self.ms))),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Wait for `future` to complete within `ms` milliseconds.
+/// Returns [`PromiseError::Timeout`] if the time runs out (`future` is dropped).
+pub fn timeout<F>(ms: u32, future: F) -> impl Future<Output = PromiseResult<F::Output>>
+where F: Future
+{
+    Timeout {
+        future: Box::pin(future),
+        sleep: Box::pin(sleep(ms)),
+        ms,
+    }
+}
@@ -9,7 +9,7 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("JsValue {0:?}")]
+    #[error("{}", crate::utils::describe(.0, 2))]
     JsValue(JsValue),
     
     #[error("Invalid interval handle")]
@@ -25,6 +25,12 @@ impl From<JsValue> for Error {
     }
 }
 
+impl From<Error> for JsValue {
+    fn from(err: Error) -> Self {
+        crate::error::Error::from(err).into()
+    }
+}
+
 pub mod native {
     //! bindings to JavaScript functions
     use js_sys::Function;
@@ -4,92 +4,1328 @@
 //! 
 
 use wasm_bindgen::prelude::*;
-use js_sys::{Uint8Array, Array};
+use wasm_bindgen_futures::JsFuture;
+use js_sys::{Uint8Array, Array, BigInt, Function, Promise};
+use thiserror::Error;
+use crate::path::{Path, PathError};
+
+/// Errors produced by the [`utils`](self) module
+#[derive(Error, Debug)]
+pub enum Error {
+    /// Error contains a JsValue
+    #[error("{}", describe(.0, 2))]
+    JsValue(JsValue),
+
+    /// Property is not defined
+    #[error("Property `{0}` is not defined")]
+    Missing(String),
+
+    /// Property value is of an unexpected type
+    #[error("Property `{prop}` must be {expected}, found {found}")]
+    Type {
+        prop: String,
+        expected: &'static str,
+        found: String,
+    },
+
+    /// Numeric property value does not fit the requested type
+    #[error("Property `{prop}` value {value} is out of range for `{ty}`")]
+    Range {
+        prop: String,
+        value: String,
+        ty: &'static str,
+    },
+
+    /// Numeric property value has a fractional part or is not finite
+    #[error("Property `{prop}` value {value} is not an integer")]
+    NotInteger {
+        prop: String,
+        value: f64,
+    },
+
+    /// Property path could not be parsed
+    #[error(transparent)]
+    Path(#[from] PathError),
+
+    /// Intermediate segment of a property path is `undefined` or `null`
+    #[error("`{path}` is undefined or null, cannot get `{key}`")]
+    Undefined {
+        path: String,
+        key: String,
+    },
+
+    /// Intermediate segment of a property path is not an object
+    #[error("`{path}` is {found}, cannot get `{key}`")]
+    NotAnObject {
+        path: String,
+        found: String,
+        key: String,
+    },
+
+    /// Property expected to contain a function does not
+    #[error("Property `{name}` is not a function")]
+    NotAFunction {
+        name: String,
+    },
+
+    /// String value is not a valid integer
+    #[error("Property `{prop}` value {value:?} is not a valid integer")]
+    InvalidInteger {
+        prop: String,
+        value: String,
+    },
+
+    /// Property value is not binary data in any of the accepted formats
+    #[error("Property `{prop}` must be {expected}, found {found}")]
+    Encoding {
+        prop: String,
+        expected: String,
+        found: String,
+    },
+
+    /// Value used as a class constructor is not a function
+    #[error("`{name}` is not a constructor, found {found}")]
+    NotAConstructor {
+        name: String,
+        found: String,
+    },
+}
+
+impl From<JsValue> for Error {
+    fn from(value: JsValue) -> Self {
+        Error::JsValue(value)
+    }
+}
+
+impl From<Error> for JsValue {
+    fn from(err: Error) -> Self {
+        crate::error::Error::from(err).into()
+    }
+}
+
+/// Largest integer that a JavaScript `number` can represent exactly
+pub const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+/// Kind of a JavaScript typed array
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TypedArrayKind {
+    Int8,
+    Uint8,
+    Uint8Clamped,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+    BigInt64,
+    BigUint64,
+}
+
+impl TypedArrayKind {
+    /// Name of the JavaScript typed array class
+    pub fn name(&self) -> &'static str {
+        match self {
+            TypedArrayKind::Int8 => "Int8Array",
+            TypedArrayKind::Uint8 => "Uint8Array",
+            TypedArrayKind::Uint8Clamped => "Uint8ClampedArray",
+            TypedArrayKind::Int16 => "Int16Array",
+            TypedArrayKind::Uint16 => "Uint16Array",
+            TypedArrayKind::Int32 => "Int32Array",
+            TypedArrayKind::Uint32 => "Uint32Array",
+            TypedArrayKind::Float32 => "Float32Array",
+            TypedArrayKind::Float64 => "Float64Array",
+            TypedArrayKind::BigInt64 => "BigInt64Array",
+            TypedArrayKind::BigUint64 => "BigUint64Array",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        let kind = match name {
+            "Int8Array" => TypedArrayKind::Int8,
+            "Uint8Array" => TypedArrayKind::Uint8,
+            "Uint8ClampedArray" => TypedArrayKind::Uint8Clamped,
+            "Int16Array" => TypedArrayKind::Int16,
+            "Uint16Array" => TypedArrayKind::Uint16,
+            "Int32Array" => TypedArrayKind::Int32,
+            "Uint32Array" => TypedArrayKind::Uint32,
+            "Float32Array" => TypedArrayKind::Float32,
+            "Float64Array" => TypedArrayKind::Float64,
+            "BigInt64Array" => TypedArrayKind::BigInt64,
+            "BigUint64Array" => TypedArrayKind::BigUint64,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+/// Type of a JavaScript value as determined by [`js_type`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JsType {
+    Undefined,
+    Null,
+    Bool,
+    Number,
+    BigInt,
+    String,
+    Symbol,
+    Function,
+    Array,
+    /// Typed array (including Node.js `Buffer`, which is a `Uint8Array`)
+    TypedArray(TypedArrayKind),
+    ArrayBuffer,
+    Promise,
+    Date,
+    Map,
+    Set,
+    /// `Error` or any of its subclasses
+    Error,
+    /// Object created by an object literal, `new Object()` or `Object.create(null)`
+    PlainObject,
+    /// Instance of a class, containing the constructor name
+    ClassInstance(String),
+}
+
+impl std::fmt::Display for JsType {
+    /// Renders the type for use in messages, e.g. `a number` or `an instance of Foo`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsType::Undefined => write!(f, "undefined"),
+            JsType::Null => write!(f, "null"),
+            JsType::Bool => write!(f, "a boolean"),
+            JsType::Number => write!(f, "a number"),
+            JsType::BigInt => write!(f, "a bigint"),
+            JsType::String => write!(f, "a string"),
+            JsType::Symbol => write!(f, "a symbol"),
+            JsType::Function => write!(f, "a function"),
+            JsType::Array => write!(f, "an array"),
+            JsType::TypedArray(kind) => write!(f, "a {}", kind.name()),
+            JsType::ArrayBuffer => write!(f, "an ArrayBuffer"),
+            JsType::Promise => write!(f, "a Promise"),
+            JsType::Date => write!(f, "a Date"),
+            JsType::Map => write!(f, "a Map"),
+            JsType::Set => write!(f, "a Set"),
+            JsType::Error => write!(f, "an Error"),
+            JsType::PlainObject => write!(f, "an object"),
+            JsType::ClassInstance(name) => write!(f, "an instance of {name}"),
+        }
+    }
+}
+
+/// Determine the [`JsType`] of a JavaScript value
+pub fn js_type(value: &JsValue) -> JsType {
+    if value.is_undefined() {
+        JsType::Undefined
+    } else if value.is_null() {
+        JsType::Null
+    } else if value.as_bool().is_some() {
+        JsType::Bool
+    } else if value.as_f64().is_some() {
+        JsType::Number
+    } else if value.is_bigint() {
+        JsType::BigInt
+    } else if value.is_string() {
+        JsType::String
+    } else if value.is_symbol() {
+        JsType::Symbol
+    } else if value.is_function() {
+        JsType::Function
+    } else if Array::is_array(value) {
+        JsType::Array
+    } else if js_sys::ArrayBuffer::is_view(value) && !value.is_instance_of::<js_sys::DataView>() {
+        // `Symbol.toStringTag` reports the typed array kind for subclasses such as `Buffer`
+        js_sys::Reflect::get(value, &js_sys::Symbol::to_string_tag())
+            .ok()
+            .and_then(|tag| tag.as_string())
+            .and_then(|tag| TypedArrayKind::from_name(&tag))
+            .map(JsType::TypedArray)
+            .unwrap_or_else(|| JsType::ClassInstance(constructor_name(value).unwrap_or_default()))
+    } else if value.is_instance_of::<js_sys::ArrayBuffer>() {
+        JsType::ArrayBuffer
+    } else if value.is_instance_of::<Promise>() {
+        JsType::Promise
+    } else if value.is_instance_of::<js_sys::Date>() {
+        JsType::Date
+    } else if value.is_instance_of::<js_sys::Map>() {
+        JsType::Map
+    } else if value.is_instance_of::<js_sys::Set>() {
+        JsType::Set
+    } else if value.is_instance_of::<js_sys::Error>() {
+        JsType::Error
+    } else {
+        let prototype = js_sys::Object::get_prototype_of(value);
+        if prototype.is_null() || constructor_name(value).as_deref() == Some("Object") {
+            JsType::PlainObject
+        } else {
+            JsType::ClassInstance(constructor_name(value).unwrap_or_else(|| "<anonymous>".to_string()))
+        }
+    }
+}
+
+/// Check if `value` is an instance of the globally accessible class
+/// identified by `path` (such as `"Uint8Array"` or `"lib.crypto.Hasher"`).
+/// Returns `false` if the class does not exist.
+pub fn is_instance_of_global(value: &JsValue, path: &str) -> bool {
+    let ctor = match resolve_global_path(path) {
+        Ok(ctor) if ctor.is_function() => ctor,
+        _ => return false,
+    };
+
+    match js_sys::Reflect::get(&ctor, &JsValue::from("prototype")) {
+        Ok(prototype) if prototype.is_object() => {
+            js_sys::Object::is_prototype_of(prototype.unchecked_ref::<js_sys::Object>(), value)
+        }
+        _ => false,
+    }
+}
+
+/// Short description of the value type used in error messages
+pub(crate) fn type_name(value: &JsValue) -> String {
+    js_type(value).to_string()
+}
+
+fn type_error(prop: &str, expected: &'static str, value: &JsValue) -> Error {
+    if value.is_undefined() {
+        Error::Missing(prop.to_string())
+    } else {
+        Error::Type {
+            prop: prop.to_string(),
+            expected,
+            found: type_name(value),
+        }
+    }
+}
+
+///
+/// Conversion of a JavaScript property value into a Rust type
+/// used by [`get`]. Integer conversions check that the value is
+/// an integer within the range of the target type (and within
+/// [`MAX_SAFE_INTEGER`] for 64 and 128-bit types).
+///
+pub trait FromJsProp: Sized {
+    /// Convert `value` obtained from the property `prop`
+    fn from_js_prop(prop: &str, value: JsValue) -> Result<Self, Error>;
+
+    /// Convert an array-like `value` obtained from the property `prop`.
+    /// Element errors refer to the element as `prop[index]`.
+    fn vec_from_js_prop(prop: &str, value: JsValue) -> Result<Vec<Self>, Error> {
+        elements_from_js_prop(prop, value)
+    }
+}
+
+/// Convert elements of an array or a typed array one by one
+fn elements_from_js_prop<T: FromJsProp>(prop: &str, value: JsValue) -> Result<Vec<T>, Error> {
+    let array = if Array::is_array(&value) {
+        value.unchecked_into::<Array>()
+    } else if js_sys::ArrayBuffer::is_view(&value) && !value.is_instance_of::<js_sys::DataView>() {
+        Array::from(&value)
+    } else {
+        return Err(type_error(prop, "an array", &value));
+    };
+
+    array
+        .iter()
+        .enumerate()
+        .map(|(index, item)| T::from_js_prop(&format!("{prop}[{index}]"), item))
+        .collect()
+}
+
+fn number(prop: &str, value: &JsValue) -> Result<f64, Error> {
+    value.as_f64().ok_or_else(|| type_error(prop, "a number", value))
+}
+
+fn integer(prop: &str, value: f64, min: f64, max: f64, ty: &'static str) -> Result<f64, Error> {
+    if !value.is_finite() || value.fract() != 0.0 {
+        Err(Error::NotInteger {
+            prop: prop.to_string(),
+            value,
+        })
+    } else if value < min.max(-MAX_SAFE_INTEGER) || value > max.min(MAX_SAFE_INTEGER) {
+        Err(Error::Range {
+            prop: prop.to_string(),
+            value: value.to_string(),
+            ty,
+        })
+    } else {
+        Ok(value)
+    }
+}
+
+macro_rules! impl_from_js_prop_for_integer {
+    ($($ty:ty)*) => ($(
+        impl FromJsProp for $ty {
+            fn from_js_prop(prop: &str, value: JsValue) -> Result<Self, Error> {
+                let v = number(prop, &value)?;
+                Ok(integer(prop, v, <$ty>::MIN as f64, <$ty>::MAX as f64, stringify!($ty))? as $ty)
+            }
+        }
+    )*)
+}
+
+impl_from_js_prop_for_integer!(i8 i16 i32 isize u16 u32 usize);
+
+/// 64 and 128-bit integers additionally accept values supported by [`to_bigint`]
+macro_rules! impl_from_js_prop_for_big_integer {
+    ($($ty:ty)*) => ($(
+        impl FromJsProp for $ty {
+            fn from_js_prop(prop: &str, value: JsValue) -> Result<Self, Error> {
+                if let Some(v) = value.as_f64() {
+                    Ok(integer(prop, v, <$ty>::MIN as f64, <$ty>::MAX as f64, stringify!($ty))? as $ty)
+                } else {
+                    let bigint = bigint_from_js_prop(prop, &value)?;
+                    bigint_to_integer(prop, &bigint, stringify!($ty))
+                }
+            }
+        }
+    )*)
+}
+
+impl_from_js_prop_for_big_integer!(i64 u64 i128 u128);
+
+impl FromJsProp for u8 {
+    fn from_js_prop(prop: &str, value: JsValue) -> Result<Self, Error> {
+        let v = number(prop, &value)?;
+        Ok(integer(prop, v, u8::MIN as f64, u8::MAX as f64, "u8")? as u8)
+    }
+
+    /// Bytes of a `Uint8Array` (including Node.js `Buffer`), `ArrayBuffer`
+    /// or `DataView`. Elements of arrays and other typed arrays are
+    /// converted one by one (and must be integers within the `u8` range).
+    fn vec_from_js_prop(prop: &str, value: JsValue) -> Result<Vec<Self>, Error> {
+        const EXPECTED: &str = "a Uint8Array, an ArrayBuffer, a DataView or an array of numbers";
+
+        let bytes = value.is_instance_of::<Uint8Array>()
+            || value.is_instance_of::<js_sys::ArrayBuffer>()
+            || value.is_instance_of::<js_sys::DataView>();
+        if bytes {
+            match crate::buffer::typed_array::<u8>(&value) {
+                Ok(array) => Ok(array.to_vec()),
+                Err(crate::buffer::Error::JsValue(err)) => Err(Error::JsValue(err)),
+                Err(_) => Err(type_error(prop, EXPECTED, &value)),
+            }
+        } else if Array::is_array(&value) || js_sys::ArrayBuffer::is_view(&value) {
+            elements_from_js_prop(prop, value)
+        } else {
+            Err(type_error(prop, EXPECTED, &value))
+        }
+    }
+}
+
+impl FromJsProp for f64 {
+    fn from_js_prop(prop: &str, value: JsValue) -> Result<Self, Error> {
+        number(prop, &value)
+    }
+}
+
+impl FromJsProp for f32 {
+    fn from_js_prop(prop: &str, value: JsValue) -> Result<Self, Error> {
+        Ok(number(prop, &value)? as f32)
+    }
+}
+
+impl FromJsProp for bool {
+    fn from_js_prop(prop: &str, value: JsValue) -> Result<Self, Error> {
+        value.as_bool().ok_or_else(|| type_error(prop, "a boolean", &value))
+    }
+}
+
+impl FromJsProp for String {
+    fn from_js_prop(prop: &str, value: JsValue) -> Result<Self, Error> {
+        value.as_string().ok_or_else(|| type_error(prop, "a string", &value))
+    }
+}
+
+impl FromJsProp for JsValue {
+    fn from_js_prop(_prop: &str, value: JsValue) -> Result<Self, Error> {
+        Ok(value)
+    }
+}
+
+/// `undefined` and `null` values produce `None`
+impl<T: FromJsProp> FromJsProp for Option<T> {
+    fn from_js_prop(prop: &str, value: JsValue) -> Result<Self, Error> {
+        if value.is_undefined() || value.is_null() {
+            Ok(None)
+        } else {
+            T::from_js_prop(prop, value).map(Some)
+        }
+    }
+}
+
+impl<T: FromJsProp> FromJsProp for Vec<T> {
+    fn from_js_prop(prop: &str, value: JsValue) -> Result<Self, Error> {
+        T::vec_from_js_prop(prop, value)
+    }
+}
+
+/// Resolve the value holding the last segment of `path`.
+pub(crate) fn lookup_parent(jsv: &JsValue, path: &Path) -> Result<JsValue, Error> {
+    let mut target = jsv.clone();
+    let segments = path.segments();
+    for (n, segment) in segments[..segments.len() - 1].iter().enumerate() {
+        let value = js_sys::Reflect::get(&target, &segment.to_js_value())?;
+        if value.is_undefined() || value.is_null() {
+            return Err(Error::Undefined {
+                path: path.prefix(n + 1),
+                key: segments[n + 1].to_string(),
+            });
+        }
+        if !(value.is_object() || value.is_function()) {
+            return Err(Error::NotAnObject {
+                path: path.prefix(n + 1),
+                found: type_name(&value),
+                key: segments[n + 1].to_string(),
+            });
+        }
+        target = value;
+    }
+
+    Ok(target)
+}
+
+/// Obtain a `JsValue` located at `path` (such as `response.data.items[0].id`).
+/// Uses the same path syntax as [`OptionsExt::set()`](crate::options::OptionsExt::set).
+/// The returned error identifies the path segment that could not be traversed.
+pub fn get_js_value_path(jsv: &JsValue, path: &str) -> Result<JsValue, Error> {
+    let path = Path::parse(path)?;
+    let parent = lookup_parent(jsv, &path)?;
+    Ok(js_sys::Reflect::get(&parent, &path.split_last().1.to_js_value())?)
+}
+
+/// Obtain a value of type `T` located at `path` (see [`get_js_value_path`]).
+/// ```ignore
+/// let id = utils::get_path::<u64>(&response, "data.items[0].id")?;
+/// ```
+pub fn get_path<T: FromJsProp>(jsv: &JsValue, path: &str) -> Result<T, Error> {
+    let value = get_js_value_path(jsv, path)?;
+    T::from_js_prop(path, value)
+}
+
+/// Obtain a value of type `T` from the object property `prop`.
+/// ```ignore
+/// let port = utils::get::<u16>(&config, "port")?;
+/// let peers = utils::get::<Option<Vec<String>>>(&config, "peers")?;
+/// ```
+pub fn get<T: FromJsProp>(jsv: &JsValue, prop: &str) -> Result<T, Error> {
+    let value = js_sys::Reflect::get(jsv, &JsValue::from(prop))?;
+    T::from_js_prop(prop, value)
+}
+
+///
+/// Conversion of Rust values into a list of JavaScript function arguments.
+/// Implemented for `()`, tuples of up to 12 `Into<JsValue>` values,
+/// slices, arrays and vectors of `Into<JsValue>` values and [`Array`].
+///
+pub trait IntoJsArgs {
+    fn into_js_args(self) -> Array;
+}
+
+impl IntoJsArgs for () {
+    fn into_js_args(self) -> Array {
+        Array::new()
+    }
+}
+
+impl IntoJsArgs for Array {
+    fn into_js_args(self) -> Array {
+        self
+    }
+}
+
+impl<T> IntoJsArgs for &[T]
+where T: Clone + Into<JsValue>
+{
+    fn into_js_args(self) -> Array {
+        self.iter().cloned().map(Into::into).collect()
+    }
+}
+
+impl<T> IntoJsArgs for Vec<T>
+where T: Into<JsValue>
+{
+    fn into_js_args(self) -> Array {
+        self.into_iter().map(Into::into).collect()
+    }
+}
+
+impl<T, const N: usize> IntoJsArgs for [T; N]
+where T: Into<JsValue>
+{
+    fn into_js_args(self) -> Array {
+        self.into_iter().map(Into::into).collect()
+    }
+}
+
+macro_rules! impl_into_js_args_for_tuple {
+    ($(
+        ($($var:ident)*)
+    )*) => ($(
+        impl<$($var,)*> IntoJsArgs for ($($var,)*)
+        where $($var: Into<JsValue>,)*
+        {
+            #[allow(non_snake_case)]
+            fn into_js_args(self) -> Array {
+                let ($($var,)*) = self;
+                let args = Array::new();
+                $(args.push(&$var.into());)*
+                args
+            }
+        }
+    )*)
+}
+
+impl_into_js_args_for_tuple! {
+    (A)
+    (A B)
+    (A B C)
+    (A B C D)
+    (A B C D E)
+    (A B C D E F)
+    (A B C D E F G)
+    (A B C D E F G H)
+    (A B C D E F G H I)
+    (A B C D E F G H I J)
+    (A B C D E F G H I J K)
+    (A B C D E F G H I J K L)
+}
+
+/// Call the method `name` of the object `this` with the given arguments.
+/// Returns [`Error::NotAFunction`] if the property is not a function.
+/// ```ignore
+/// let ret = utils::call_method(&object, "transfer", (address, amount, true))?;
+/// ```
+pub fn call_method(this: &JsValue, name: &str, args: impl IntoJsArgs) -> Result<JsValue, Error> {
+    let fn_jsv = js_sys::Reflect::get(this, &JsValue::from(name))?;
+    if !fn_jsv.is_function() {
+        return Err(Error::NotAFunction {
+            name: name.to_string(),
+        });
+    }
+
+    Ok(js_sys::Reflect::apply(fn_jsv.unchecked_ref::<Function>(), this, &args.into_js_args())?)
+}
+
+/// Call the method `name` of the object `this` and await the result
+/// if the method returns a `Promise` (or a thenable object).
+/// A rejected promise produces [`Error::JsValue`] with the rejection value.
+pub async fn call_method_async(this: &JsValue, name: &str, args: impl IntoJsArgs) -> Result<JsValue, Error> {
+    let ret = call_method(this, name, args)?;
+    Ok(JsFuture::from(Promise::resolve(&ret)).await?)
+}
+
+/// Create a new instance of the JavaScript class `ctor` (equivalent of `new ctor(...args)`).
+/// Classes exported by modules registered via [`init_workflow()`](crate::init::init_workflow)
+/// can be resolved with [`get_js_value_path`] applied to [`modules()`](crate::init::modules).
+pub fn construct(ctor: &JsValue, args: impl IntoJsArgs) -> Result<JsValue, Error> {
+    if !ctor.is_function() {
+        return Err(Error::NotAConstructor {
+            name: describe(ctor, 0),
+            found: type_name(ctor),
+        });
+    }
+
+    Ok(js_sys::Reflect::construct(ctor.unchecked_ref::<Function>(), &args.into_js_args())?)
+}
+
+/// Create a new instance of a globally accessible JavaScript class
+/// identified by `path`, for example `construct_global("Map", ())`
+/// or `construct_global("lib.crypto.Hasher", ("sha256",))`.
+pub fn construct_global(path: &str, args: impl IntoJsArgs) -> Result<JsValue, Error> {
+    let ctor = resolve_global_path(path)?;
+    if !ctor.is_function() {
+        return Err(Error::NotAConstructor {
+            name: path.to_string(),
+            found: type_name(&ctor),
+        });
+    }
+
+    construct(&ctor, args)
+}
+
+/// Resolve a value located at `path` (such as `a.b.C`) relative to the
+/// JavaScript global object. Returns [`Error::Missing`] if the value is `undefined`.
+pub fn resolve_global_path(path: &str) -> Result<JsValue, Error> {
+    let value = get_js_value_path(&js_sys::global(), path)?;
+    if value.is_undefined() {
+        Err(Error::Missing(path.to_string()))
+    } else {
+        Ok(value)
+    }
+}
 
 /// Call a JavaScript function without arguments 
 pub fn apply_with_args0(this_jsv: &JsValue, fn_name: &str) -> Result<JsValue,JsValue> {
-    let fn_jsv = js_sys::Reflect::get(&this_jsv,&JsValue::from(fn_name))?;
-    let args = Array::new();
-    let ret_jsv = js_sys::Reflect::apply(&fn_jsv.into(),&this_jsv,&args.into())?;
-    Ok(ret_jsv)
+    Ok(call_method(this_jsv, fn_name, ())?)
 }
 
 /// Call a JavaScript function with a single argument
 pub fn apply_with_args1(this_jsv: &JsValue, fn_name: &str, arg_jsv : JsValue) -> Result<JsValue,JsValue> {
-    let fn_jsv = js_sys::Reflect::get(&this_jsv,&JsValue::from(fn_name))?;
-    let args = Array::new_with_length(1);
-    args.set(0, arg_jsv);
-    let ret_jsv = js_sys::Reflect::apply(&fn_jsv.into(),&this_jsv,&args.into())?;
-    Ok(ret_jsv)
+    Ok(call_method(this_jsv, fn_name, (arg_jsv,))?)
 }
 
 /// Call a JavaScript function with two arguments
 pub fn apply_with_args2(this_jsv: &JsValue, fn_name: &str, arg_jsv : JsValue, arg2_jsv : JsValue) -> Result<JsValue,JsValue> {
-    let fn_jsv = js_sys::Reflect::get(&this_jsv,&JsValue::from(fn_name))?;
-    let args = Array::new_with_length(2);
-    args.set(0, arg_jsv);
-    args.set(1, arg2_jsv);
-    let ret_jsv = js_sys::Reflect::apply(&fn_jsv.into(),&this_jsv,&args.into())?;
-    Ok(ret_jsv)
+    Ok(call_method(this_jsv, fn_name, (arg_jsv, arg2_jsv))?)
 }
 
 /// Obtain a `u64` value from an object property.
-/// Returns successfully parsed value or 0.
+/// (see [`get`] for typed access with structured errors)
 pub fn try_get_u64_from_prop(jsv : &JsValue, prop : &str) -> Result<u64,JsValue> {
-    let v = js_sys::Reflect::get(&jsv,&JsValue::from(prop))?;
-    Ok(v.as_f64()
-        .ok_or(JsValue::from(format!("try_get_u64(): error parsing property '{}' with value '{:?}'",prop,v)))?
-        as u64
-    )
+    Ok(get::<u64>(jsv, prop)?)
 }
 
 /// Obtain `f64` value from an object property.
 pub fn try_get_f64_from_prop(jsv : &JsValue, prop : &str) -> Result<f64,JsValue> {
-    let v = js_sys::Reflect::get(&jsv,&JsValue::from(prop))?;
-    Ok(v.as_f64()
-        .ok_or(JsValue::from(format!("try_get_f64(): error parsing property '{}' with value '{:?}'",prop,v)))?
-    )
+    Ok(get::<f64>(jsv, prop)?)
 }
 
 /// Obtain `u8` value from the object property `prop`.
 pub fn try_get_u8_from_prop(jsv : &JsValue, prop : &str) -> Result<u8,JsValue> {
-    let v = js_sys::Reflect::get(&jsv,&JsValue::from(prop))?;
-    Ok(v.as_f64()
-        .ok_or(JsValue::from(format!("try_get_u8(): error parsing property '{}' with value '{:?}'",prop,v)))?
-        as u8
-    )
+    Ok(get::<u8>(jsv, prop)?)
 }
 
 /// Obtain a `bool` value from the object property `prop`
 pub fn try_get_bool_from_prop(jsv : &JsValue, prop : &str) -> Result<bool,JsValue> {
-    Ok(js_sys::Reflect::get(&jsv,&JsValue::from(prop))?.as_bool()
-        .ok_or(JsValue::from(format!("try_get_bool(): property {} is missing or not a boolean",prop)))?
-    )
+    Ok(get::<bool>(jsv, prop)?)
 }
 
-/// Obtain a `Vec<u8>` value from the object property `prop` (using `Uint8Array`)
+/// Obtain a `Vec<u8>` value from the object property `prop` containing
+/// a buffer-like value (see [`buffer::typed_array()`](crate::buffer::typed_array)).
 pub fn try_get_vec_from_prop(jsv : &JsValue, prop : &str) -> Result<Vec<u8>,JsValue> {
-    let buffer = js_sys::Reflect::get(&jsv,&JsValue::from(prop))?;
-    let array = Uint8Array::new(&buffer);
-    let data: Vec<u8> = array.to_vec();
-    Ok(data)
+    let buffer = js_sys::Reflect::get(jsv, &JsValue::from(prop))?;
+    Ok(crate::buffer::to_vec::<u8>(&buffer)?)
 }
 
 /// Obtain a `Vec<u8>` from the property `prop` expressed as a big number
+/// (see [`try_get_vec_from_bn`])
 pub fn try_get_vec_from_bn_prop(object_jsv : &JsValue, prop : &str) -> Result<Vec<u8>,JsValue> {
 
     let bn_jsv = js_sys::Reflect::get(&object_jsv,&JsValue::from(prop))?;
-    let bytes = apply_with_args0(&bn_jsv, "toBytes" )?;
-    let array = Uint8Array::new(&bytes);
-    Ok(array.to_vec())
+    try_get_vec_from_bn(&bn_jsv)
 }
 
-/// Obtain `Vec<u8>` from the supplied big number
+/// Obtain big-endian `Vec<u8>` from the supplied big number. Objects providing
+/// a `toBytes()` method are converted using that method, any other value
+/// supported by [`to_bigint`] is converted using [`bigint_to_bytes_be`].
 pub fn try_get_vec_from_bn(bn_jsv : &JsValue) -> Result<Vec<u8>, JsValue> {
 
-    let bytes = apply_with_args0(&bn_jsv, "toBytes" )?;
-    let array = Uint8Array::new(&bytes);
-    Ok(array.to_vec())
+    let to_bytes = js_sys::Reflect::get(bn_jsv, &JsValue::from("toBytes"))?;
+    if to_bytes.is_function() {
+        let bytes = apply_with_args0(bn_jsv, "toBytes")?;
+        let array = Uint8Array::new(&bytes);
+        Ok(array.to_vec())
+    } else {
+        Ok(bigint_to_bytes_be(bn_jsv)?)
+    }
+}
+
+/// Check if the value is a big number object (such as a bn.js instance)
+/// providing `toString(radix)` along with `toArray()` or `toBytes()`.
+fn is_big_number_object(value: &JsValue) -> bool {
+    let has_fn = |name: &str| {
+        js_sys::Reflect::get(value, &JsValue::from(name))
+            .map(|f| f.is_function())
+            .unwrap_or(false)
+    };
+    value.is_object() && has_fn("toString") && (has_fn("toArray") || has_fn("toBytes"))
+}
+
+fn bigint_from_js_prop(prop: &str, value: &JsValue) -> Result<BigInt, Error> {
+    if value.is_bigint() {
+        return Ok(value.clone().unchecked_into());
+    }
+
+    if let Some(v) = value.as_f64() {
+        integer(prop, v, -MAX_SAFE_INTEGER, MAX_SAFE_INTEGER, "BigInt")?;
+        return Ok(BigInt::new(value).map_err(JsValue::from)?);
+    }
+
+    let text = if let Some(text) = value.as_string() {
+        text.trim().to_string()
+    } else if is_big_number_object(value) {
+        call_method(value, "toString", (10,))?
+            .as_string()
+            .unwrap_or_default()
+    } else {
+        return Err(type_error(prop, "a BigInt, a big number, a numeric string or an integer", value));
+    };
+
+    if text.is_empty() {
+        return Err(Error::InvalidInteger {
+            prop: prop.to_string(),
+            value: text,
+        });
+    }
+
+    BigInt::new(&JsValue::from(text.as_str())).map_err(|_| Error::InvalidInteger {
+        prop: prop.to_string(),
+        value: text,
+    })
+}
+
+fn bigint_to_integer<T>(prop: &str, bigint: &BigInt, ty: &'static str) -> Result<T, Error>
+where T: TryFrom<i128> + TryFrom<u128>
+{
+    let text = String::from(bigint.to_string(10).map_err(JsValue::from)?);
+    let range = || Error::Range {
+        prop: prop.to_string(),
+        value: text.clone(),
+        ty,
+    };
+
+    if text.starts_with('-') {
+        let v = text.parse::<i128>().map_err(|_| range())?;
+        T::try_from(v).map_err(|_| range())
+    } else {
+        let v = text.parse::<u128>().map_err(|_| range())?;
+        T::try_from(v).map_err(|_| range())
+    }
+}
+
+/// Convert a `BigInt`, a big number object (such as a bn.js instance providing
+/// `toString(radix)` along with `toArray()` or `toBytes()`), a numeric string
+/// (decimal or `0x`-prefixed hexadecimal) or a safe-integer number into a [`BigInt`].
+/// Rust integers can be converted into a [`BigInt`] using `BigInt::from()`.
+pub fn to_bigint(value: &JsValue) -> Result<BigInt, Error> {
+    bigint_from_js_prop("<value>", value)
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Rust integer types supported by [`bigint_to`] (implemented for all
+/// primitive integer types and can not be implemented outside of this crate)
+pub trait BigIntInteger: sealed::Sealed + TryFrom<i128> + TryFrom<u128> {
+    /// Name of the type used in error messages
+    const TYPE_NAME: &'static str;
+}
+
+macro_rules! impl_bigint_integer {
+    ($($ty:ty)*) => ($(
+        impl sealed::Sealed for $ty {}
+        impl BigIntInteger for $ty {
+            const TYPE_NAME: &'static str = stringify!($ty);
+        }
+    )*)
+}
+
+impl_bigint_integer!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+/// Convert a value supported by [`to_bigint`] into a Rust integer, producing
+/// [`Error::Range`] if the value does not fit into `T`.
+/// ```ignore
+/// let amount = utils::bigint_to::<u64>(&value)?;
+/// ```
+pub fn bigint_to<T: BigIntInteger>(value: &JsValue) -> Result<T, Error> {
+    let bigint = bigint_from_js_prop("<value>", value)?;
+    bigint_to_integer("<value>", &bigint, T::TYPE_NAME)
+}
+
+/// Convert a non-negative value supported by [`to_bigint`]
+/// into a minimal big-endian byte vector (zero produces `[0]`).
+pub fn bigint_to_bytes_be(value: &JsValue) -> Result<Vec<u8>, Error> {
+    let bigint = to_bigint(value)?;
+    let hex = String::from(bigint.to_string(16).map_err(JsValue::from)?);
+    if hex.starts_with('-') {
+        return Err(Error::Range {
+            prop: "<value>".to_string(),
+            value: String::from(bigint.to_string(10).map_err(JsValue::from)?),
+            ty: "unsigned bytes",
+        });
+    }
+
+    let hex = if hex.len() % 2 == 1 { format!("0{hex}") } else { hex };
+    Ok((0..hex.len())
+        .step_by(2)
+        .map(|n| u8::from_str_radix(&hex[n..n + 2], 16).expect("BigInt produces valid hex"))
+        .collect())
+}
+
+/// Convert a non-negative value supported by [`to_bigint`]
+/// into a minimal little-endian byte vector (zero produces `[0]`).
+pub fn bigint_to_bytes_le(value: &JsValue) -> Result<Vec<u8>, Error> {
+    let mut bytes = bigint_to_bytes_be(value)?;
+    bytes.reverse();
+    Ok(bytes)
+}
+
+/// Create a non-negative [`BigInt`] from big-endian bytes
+pub fn bigint_from_bytes_be(bytes: &[u8]) -> Result<BigInt, Error> {
+    let hex = bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+    let hex = if hex.is_empty() { "0x0".to_string() } else { format!("0x{hex}") };
+    Ok(BigInt::new(&JsValue::from(hex)).map_err(JsValue::from)?)
+}
+
+/// Create a non-negative [`BigInt`] from little-endian bytes
+pub fn bigint_from_bytes_le(bytes: &[u8]) -> Result<BigInt, Error> {
+    let bytes = bytes.iter().rev().copied().collect::<Vec<u8>>();
+    bigint_from_bytes_be(&bytes)
+}
+
+/// Maximum number of array elements or object entries rendered by [`describe`]
+const DESCRIBE_MAX_ITEMS: usize = 16;
+/// Maximum number of string characters rendered by [`describe`]
+const DESCRIBE_MAX_STRING: usize = 64;
+
+///
+/// Render a bounded, cycle-safe, JSON-like description of a JavaScript value
+/// for use in error and log messages. Objects and arrays are expanded up to
+/// `depth` levels and a limited number of entries. Functions, symbols, BigInts,
+/// typed arrays, buffers, errors and other built-in objects are rendered
+/// using type tags, e.g. `[Function foo]`, `123n` or `Uint8Array(32)`.
+/// ```ignore
+/// // {id: 1, name: "alice", data: Uint8Array(32), next: {…}}
+/// let text = utils::describe(&value, 1);
+/// ```
+///
+pub fn describe(value: &JsValue, depth: usize) -> String {
+    let mut out = String::new();
+    let mut ancestors = Vec::new();
+    describe_into(&mut out, value, depth, &mut ancestors);
+    out
+}
+
+fn describe_into(out: &mut String, value: &JsValue, depth: usize, ancestors: &mut Vec<JsValue>) {
+    use std::fmt::Write;
+
+    if value.is_undefined() {
+        out.push_str("undefined");
+    } else if value.is_null() {
+        out.push_str("null");
+    } else if let Some(b) = value.as_bool() {
+        let _ = write!(out, "{b}");
+    } else if let Some(n) = value.as_f64() {
+        let _ = write!(out, "{}", describe_number(n));
+    } else if let Some(s) = value.as_string() {
+        let truncated = s.chars().count() > DESCRIBE_MAX_STRING;
+        let s = s.chars().take(DESCRIBE_MAX_STRING).collect::<String>();
+        let _ = write!(out, "{s:?}");
+        if truncated {
+            out.push('…');
+        }
+    } else if value.is_bigint() {
+        let bigint = value.unchecked_ref::<BigInt>();
+        let _ = write!(out, "{}n", bigint.to_string(10).map(String::from).unwrap_or_default());
+    } else if value.is_symbol() {
+        let _ = write!(out, "{}", String::from(value.unchecked_ref::<js_sys::Symbol>().to_string()));
+    } else if value.is_function() {
+        let name = String::from(value.unchecked_ref::<Function>().name());
+        if name.is_empty() {
+            out.push_str("[Function]");
+        } else {
+            let _ = write!(out, "[Function {name}]");
+        }
+    } else if ancestors.iter().any(|ancestor| ancestor == value) {
+        out.push_str("[Circular]");
+    } else if let Some(err) = value.dyn_ref::<js_sys::Error>() {
+        let _ = write!(out, "{}: {}", String::from(err.name()), String::from(err.message()));
+    } else if let Some(buffer) = value.dyn_ref::<js_sys::ArrayBuffer>() {
+        let _ = write!(out, "ArrayBuffer({})", buffer.byte_length());
+    } else if let Some(view) = value.dyn_ref::<js_sys::DataView>() {
+        let _ = write!(out, "DataView({})", view.byte_length());
+    } else if js_sys::ArrayBuffer::is_view(value) {
+        let length = js_sys::Reflect::get(value, &JsValue::from("length"))
+            .ok()
+            .and_then(|length| length.as_f64())
+            .unwrap_or(0.0);
+        let _ = write!(out, "{}({length})", constructor_name(value).unwrap_or_else(|| "TypedArray".to_string()));
+    } else if let Some(date) = value.dyn_ref::<js_sys::Date>() {
+        if date.get_time().is_nan() {
+            out.push_str("Date(Invalid)");
+        } else {
+            let _ = write!(out, "Date({})", String::from(date.to_iso_string()));
+        }
+    } else if value.is_instance_of::<Promise>() {
+        out.push_str("Promise");
+    } else if let Some(map) = value.dyn_ref::<js_sys::Map>() {
+        let _ = write!(out, "Map({})", map.size());
+    } else if let Some(set) = value.dyn_ref::<js_sys::Set>() {
+        let _ = write!(out, "Set({})", set.size());
+    } else if Array::is_array(value) {
+        let array = value.unchecked_ref::<Array>();
+        let length = array.length() as usize;
+        if depth == 0 {
+            let _ = write!(out, "Array({length})");
+            return;
+        }
+        ancestors.push(value.clone());
+        out.push('[');
+        for (n, item) in array.iter().take(DESCRIBE_MAX_ITEMS).enumerate() {
+            if n > 0 {
+                out.push_str(", ");
+            }
+            describe_into(out, &item, depth - 1, ancestors);
+        }
+        if length > DESCRIBE_MAX_ITEMS {
+            let _ = write!(out, ", … {} more", length - DESCRIBE_MAX_ITEMS);
+        }
+        out.push(']');
+        ancestors.pop();
+    } else {
+        if let Some(name) = constructor_name(value).filter(|name| name != "Object") {
+            let _ = write!(out, "{name} ");
+        }
+        if depth == 0 {
+            out.push_str("{…}");
+            return;
+        }
+        let keys = js_sys::Object::keys(value.unchecked_ref::<js_sys::Object>());
+        let length = keys.length() as usize;
+        ancestors.push(value.clone());
+        out.push('{');
+        for (n, key) in keys.iter().take(DESCRIBE_MAX_ITEMS).enumerate() {
+            if n > 0 {
+                out.push_str(", ");
+            }
+            let _ = write!(out, "{}: ", key.as_string().unwrap_or_default());
+            match js_sys::Reflect::get(value, &key) {
+                Ok(item) => describe_into(out, &item, depth - 1, ancestors),
+                Err(_) => out.push_str("<error>"),
+            }
+        }
+        if length > DESCRIBE_MAX_ITEMS {
+            let _ = write!(out, ", … {} more", length - DESCRIBE_MAX_ITEMS);
+        }
+        out.push('}');
+        ancestors.pop();
+    }
+}
+
+fn describe_number(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".to_string()
+    } else if n.is_infinite() {
+        if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else {
+        n.to_string()
+    }
+}
+
+/// Name of the value's constructor (`value.constructor.name`)
+fn constructor_name(value: &JsValue) -> Option<String> {
+    let ctor = js_sys::Reflect::get(value, &JsValue::from("constructor")).ok()?;
+    if !ctor.is_function() {
+        return None;
+    }
+    let name = String::from(ctor.unchecked_ref::<Function>().name());
+    if name.is_empty() { None } else { Some(name) }
+}
+
+/// Representation of binary data as a JavaScript value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BytesFormat {
+    /// Hex string, optionally `0x`-prefixed
+    Hex,
+    /// Base64 string (standard or URL-safe alphabet, padding optional)
+    Base64,
+    /// `Uint8Array`, other `ArrayBuffer` views or an array of numbers
+    /// (see [`buffer::typed_array()`](crate::buffer::typed_array))
+    Buffer,
+}
+
+impl std::fmt::Display for BytesFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BytesFormat::Hex => "a hex string",
+            BytesFormat::Base64 => "a base64 string",
+            BytesFormat::Buffer => "an ArrayBuffer, a typed array, a DataView or an array of numbers",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl BytesFormat {
+    /// Decode `value` if it is in this format
+    fn decode(&self, value: &JsValue) -> Option<Vec<u8>> {
+        match self {
+            BytesFormat::Hex => {
+                let text = value.as_string()?;
+                let text = text.strip_prefix("0x").unwrap_or(&text);
+                hex::decode(text).ok()
+            }
+            BytesFormat::Base64 => {
+                use base64::Engine;
+                let text = value.as_string()?;
+                let alphabet = if text.contains(['-', '_']) {
+                    &base64::alphabet::URL_SAFE
+                } else {
+                    &base64::alphabet::STANDARD
+                };
+                let config = base64::engine::GeneralPurposeConfig::new()
+                    .with_decode_padding_mode(base64::engine::DecodePaddingMode::Indifferent);
+                base64::engine::GeneralPurpose::new(alphabet, config).decode(text).ok()
+            }
+            BytesFormat::Buffer => {
+                if value.is_string() {
+                    None
+                } else {
+                    crate::buffer::to_vec::<u8>(value).ok()
+                }
+            }
+        }
+    }
+
+    /// Encode `bytes` into a JavaScript value (`Uint8Array` for [`BytesFormat::Buffer`])
+    pub fn encode(&self, bytes: &[u8]) -> JsValue {
+        match self {
+            BytesFormat::Hex => JsValue::from(hex::encode(bytes)),
+            BytesFormat::Base64 => {
+                use base64::Engine;
+                JsValue::from(base64::engine::general_purpose::STANDARD.encode(bytes))
+            }
+            BytesFormat::Buffer => Uint8Array::from(bytes).into(),
+        }
+    }
+}
+
+///
+/// Rules for detecting the format of binary data received from JavaScript.
+/// Formats are attempted in order; the first format that successfully
+/// decodes the value is used. Since a string such as `"deadbeef"` is both
+/// valid hex and valid base64, the order determines how it is interpreted.
+///
+/// The default detection accepts buffers, hex and base64 (in that order).
+/// Empty strings (and a bare `0x`) are rejected unless allowed
+/// using [`allow_empty()`](BytesDetection::allow_empty).
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytesDetection {
+    formats: Vec<BytesFormat>,
+    allow_empty: bool,
+}
+
+impl Default for BytesDetection {
+    fn default() -> Self {
+        Self::new(&[BytesFormat::Buffer, BytesFormat::Hex, BytesFormat::Base64])
+    }
+}
+
+impl BytesDetection {
+    /// Create detection rules accepting `formats` in the order of priority
+    pub fn new(formats: &[BytesFormat]) -> Self {
+        Self {
+            formats: formats.to_vec(),
+            allow_empty: false,
+        }
+    }
+
+    /// Accept empty hex and base64 strings as zero bytes
+    pub fn allow_empty(mut self, allow: bool) -> Self {
+        self.allow_empty = allow;
+        self
+    }
+
+    /// Accepted formats
+    pub fn formats(&self) -> &[BytesFormat] {
+        &self.formats
+    }
+
+    /// Decode `value` obtained from the property `prop`
+    pub fn decode(&self, prop: &str, value: &JsValue) -> Result<Vec<u8>, Error> {
+        if value.is_undefined() {
+            return Err(Error::Missing(prop.to_string()));
+        }
+
+        let expected = || {
+            self.formats
+                .iter()
+                .map(|format| format.to_string())
+                .collect::<Vec<_>>()
+                .join(" or ")
+        };
+
+        if !self.allow_empty && matches!(value.as_string().as_deref(), Some("" | "0x")) {
+            return Err(Error::Encoding {
+                prop: prop.to_string(),
+                expected: expected(),
+                found: "an empty string".to_string(),
+            });
+        }
+
+        if let Some(bytes) = self.formats.iter().find_map(|format| format.decode(value)) {
+            return Ok(bytes);
+        }
+
+        let found = if value.is_string() {
+            "a string in a different format".to_string()
+        } else {
+            type_name(value)
+        };
+        Err(Error::Encoding {
+            prop: prop.to_string(),
+            expected: expected(),
+            found,
+        })
+    }
+}
+
+/// Obtain a `Vec<u8>` from the property `prop` containing a hex string, base64 string,
+/// buffer (`ArrayBuffer`, typed array, `DataView`) or an array of numbers
+/// (using the default [`BytesDetection`]).
+pub fn get_bytes(jsv: &JsValue, prop: &str) -> Result<Vec<u8>, Error> {
+    get_bytes_with(jsv, prop, &BytesDetection::default())
+}
+
+/// Obtain a `Vec<u8>` from the property `prop` using the supplied [`BytesDetection`] rules.
+/// ```ignore
+/// let hex_only = BytesDetection::new(&[BytesFormat::Hex]);
+/// let key = utils::get_bytes_with(&args, "key", &hex_only)?;
+/// ```
+pub fn get_bytes_with(jsv: &JsValue, prop: &str, detection: &BytesDetection) -> Result<Vec<u8>, Error> {
+    let value = js_sys::Reflect::get(jsv, &JsValue::from(prop))?;
+    detection.decode(prop, &value)
+}
+
+/// Set the property `prop` to `bytes` encoded in the given `format`
+pub fn set_bytes(jsv: &JsValue, prop: &str, bytes: &[u8], format: BytesFormat) -> Result<(), Error> {
+    js_sys::Reflect::set(jsv, &JsValue::from(prop), &format.encode(bytes))?;
+    Ok(())
+}
+
+/// Own enumerable property names of an object (`Object.keys()`)
+pub fn keys(jsv: &JsValue) -> Result<Vec<String>, Error> {
+    Ok(entries(jsv)?.into_iter().map(|(key, _)| key).collect())
+}
+
+/// Own enumerable properties of an object as key/value pairs (`Object.entries()`)
+pub fn entries(jsv: &JsValue) -> Result<Vec<(String, JsValue)>, Error> {
+    if !(jsv.is_object() || jsv.is_function()) {
+        return Err(type_error("<value>", "an object", jsv));
+    }
+
+    js_sys::Object::keys(jsv.unchecked_ref::<js_sys::Object>())
+        .iter()
+        .map(|key| {
+            let value = js_sys::Reflect::get(jsv, &key)?;
+            Ok((key.as_string().unwrap_or_default(), value))
+        })
+        .collect()
+}
+
+/// Convert own enumerable properties of an object into a collection
+/// of `(String, T)` pairs. Conversion errors refer to the property name.
+fn object_to_collection<T, C>(jsv: &JsValue) -> Result<C, Error>
+where
+    T: FromJsProp,
+    C: FromIterator<(String, T)>,
+{
+    entries(jsv)?
+        .into_iter()
+        .map(|(key, value)| {
+            let value = T::from_js_prop(&key, value)?;
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Convert an object into a [`HashMap`](std::collections::HashMap) with values of type `T`
+pub fn to_hash_map<T: FromJsProp>(jsv: &JsValue) -> Result<std::collections::HashMap<String, T>, Error> {
+    object_to_collection(jsv)
+}
+
+/// Convert an object into a [`BTreeMap`](std::collections::BTreeMap) with values of type `T`
+pub fn to_btree_map<T: FromJsProp>(jsv: &JsValue) -> Result<std::collections::BTreeMap<String, T>, Error> {
+    object_to_collection(jsv)
+}
+
+/// Create a JavaScript object from `(key, value)` pairs
+pub fn to_js_object<I, K, V>(iter: I) -> Result<js_sys::Object, Error>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: Into<JsValue>,
+{
+    let object = js_sys::Object::new();
+    for (key, value) in iter {
+        js_sys::Reflect::set(&object, &JsValue::from(key.as_ref()), &value.into())?;
+    }
+    Ok(object)
+}
+
+/// Convert a JavaScript `Map` into a collection of `(K, V)` pairs.
+/// Conversion errors refer to the entry as `[key]`.
+pub fn js_map_to<K, V, C>(jsv: &JsValue) -> Result<C, Error>
+where
+    K: FromJsProp,
+    V: FromJsProp,
+    C: FromIterator<(K, V)>,
+{
+    let map = jsv
+        .dyn_ref::<js_sys::Map>()
+        .ok_or_else(|| type_error("<value>", "a Map", jsv))?;
+
+    let mut items = Vec::with_capacity(map.size() as usize);
+    map.for_each(&mut |value, key| items.push((key, value)));
+    items
+        .into_iter()
+        .map(|(key, value)| {
+            let label = format!("[{}]", describe(&key, 0));
+            Ok((K::from_js_prop(&label, key)?, V::from_js_prop(&label, value)?))
+        })
+        .collect()
+}
+
+/// Create a JavaScript `Map` from `(key, value)` pairs
+pub fn to_js_map<I, K, V>(iter: I) -> js_sys::Map
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<JsValue>,
+    V: Into<JsValue>,
+{
+    let map = js_sys::Map::new();
+    for (key, value) in iter {
+        map.set(&key.into(), &value.into());
+    }
+    map
+}
+
+/// Convert a JavaScript `Set` into a collection of `T`.
+/// Conversion errors refer to the element as `[index]` in iteration order.
+pub fn js_set_to<T, C>(jsv: &JsValue) -> Result<C, Error>
+where
+    T: FromJsProp,
+    C: FromIterator<T>,
+{
+    let set = jsv
+        .dyn_ref::<js_sys::Set>()
+        .ok_or_else(|| type_error("<value>", "a Set", jsv))?;
+
+    let mut items = Vec::with_capacity(set.size() as usize);
+    set.for_each(&mut |value, _, _| items.push(value));
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| T::from_js_prop(&format!("[{index}]"), value))
+        .collect()
+}
+
+/// Create a JavaScript `Set` from values
+pub fn to_js_set<I, T>(iter: I) -> js_sys::Set
+where
+    I: IntoIterator<Item = T>,
+    T: Into<JsValue>,
+{
+    let set = js_sys::Set::new(&JsValue::UNDEFINED);
+    for value in iter {
+        set.add(&value.into());
+    }
+    set
+}
+
+/// Convert a JavaScript array (or typed array) into a `Vec<T>`.
+/// Conversion errors identify the failing element as `<value>[index]`.
+pub fn to_vec<T: FromJsProp>(jsv: &JsValue) -> Result<Vec<T>, Error> {
+    T::vec_from_js_prop("<value>", jsv.clone())
+}
+
+/// Create a JavaScript array from values
+pub fn to_js_array<I, T>(iter: I) -> Array
+where
+    I: IntoIterator<Item = T>,
+    T: Into<JsValue>,
+{
+    iter.into_iter().map(Into::into).collect()
 }
 
 /// Obtain a `String` value from the object property `prop`
@@ -98,7 +1334,7 @@ pub fn try_get_string(jsv : &JsValue, prop : &str) -> Result<String, JsValue> {
     match str.as_string(){
         Some(str)=>Ok(str),
         None=>{
-            return Err(JsValue::from(format!("Unable to find property '{}' on object '{:?}'", prop, jsv)));
+            return Err(JsValue::from(format!("Unable to find property '{}' on object {}", prop, describe(jsv, 1))));
         }
     }
 }
@@ -109,3 +1345,62 @@ pub fn try_get_js_value(this_jsv:&JsValue, prop:&str)->Result<JsValue,JsValue> {
     Ok(v)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn not_integer(value: f64) -> bool {
+        matches!(integer("v", value, i32::MIN as f64, i32::MAX as f64, "i32"), Err(Error::NotInteger { .. }))
+    }
+
+    fn out_of_range(value: f64, min: f64, max: f64) -> bool {
+        matches!(integer("v", value, min, max, "ty"), Err(Error::Range { .. }))
+    }
+
+    #[test]
+    fn integer_rejects_non_integers() {
+        assert!(not_integer(f64::NAN));
+        assert!(not_integer(f64::INFINITY));
+        assert!(not_integer(f64::NEG_INFINITY));
+        assert!(not_integer(1.5));
+        assert!(not_integer(-0.25));
+        assert_eq!(integer("v", -7.0, i32::MIN as f64, i32::MAX as f64, "i32").unwrap(), -7.0);
+    }
+
+    #[test]
+    fn integer_checks_range() {
+        assert!(out_of_range(-1.0, u32::MIN as f64, u32::MAX as f64));
+        assert!(out_of_range(-1.0, u64::MIN as f64, u64::MAX as f64));
+        assert!(out_of_range(u8::MAX as f64 + 1.0, u8::MIN as f64, u8::MAX as f64));
+        assert!(out_of_range(i8::MIN as f64 - 1.0, i8::MIN as f64, i8::MAX as f64));
+        assert_eq!(integer("v", 255.0, u8::MIN as f64, u8::MAX as f64, "u8").unwrap(), 255.0);
+        assert_eq!(integer("v", 0.0, u8::MIN as f64, u8::MAX as f64, "u8").unwrap(), 0.0);
+
+        match integer("v", 256.0, u8::MIN as f64, u8::MAX as f64, "u8") {
+            Err(Error::Range { prop, value, ty }) => {
+                assert_eq!((prop.as_str(), value.as_str(), ty), ("v", "256", "u8"));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn integer_clamps_to_safe_integers() {
+        let bounds = [
+            (i64::MIN as f64, i64::MAX as f64),
+            (u64::MIN as f64, u64::MAX as f64),
+            (i128::MIN as f64, i128::MAX as f64),
+            (u128::MIN as f64, u128::MAX as f64),
+        ];
+        for (min, max) in bounds {
+            assert_eq!(integer("v", MAX_SAFE_INTEGER, min, max, "ty").unwrap(), MAX_SAFE_INTEGER);
+            assert!(out_of_range(MAX_SAFE_INTEGER + 1.0, min, max));
+            assert!(out_of_range(u64::MAX as f64, min, max));
+        }
+
+        assert_eq!(integer("v", -MAX_SAFE_INTEGER, i64::MIN as f64, i64::MAX as f64, "i64").unwrap(), -MAX_SAFE_INTEGER);
+        assert!(out_of_range(-MAX_SAFE_INTEGER - 1.0, i64::MIN as f64, i64::MAX as f64));
+        assert!(out_of_range(-MAX_SAFE_INTEGER - 1.0, i128::MIN as f64, i128::MAX as f64));
+    }
+}